@@ -0,0 +1,235 @@
+use crate::{
+    mods::{Compatibility, Mod, ModEffect, ModStat, Rarity},
+    public_export::{convert, relic_arcane::RelicArcane},
+    weapon::Weapon,
+};
+
+use std::{collections::HashMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+/// Weapon arcanes apply to a `Loadout`'s hits. Warframe arcanes only apply to
+/// the stats of a `WarframeLoadout`, as their effects on weapons are not
+/// modeled.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
+pub enum ArcaneSlot {
+    #[default]
+    Weapon,
+    Warframe,
+}
+
+/// An arcane, whose mostly conditional stats apply like those of a mod.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+pub struct Arcane {
+    pub name: String,
+    pub slot: ArcaneSlot,
+    /// Weapons a weapon arcane can be equipped on.
+    #[serde(default)]
+    pub compatibility: Compatibility,
+    /// Stats at `max_rank`.
+    pub stats: Vec<ModStat>,
+    /// Effects at every rank, as in `Mod::ranks`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ranks: Vec<Vec<ModEffect>>,
+    pub max_rank: u32,
+    /// Current rank, `None` meaning fully ranked.
+    #[serde(default)]
+    pub rank: Option<u32>,
+    #[serde(default)]
+    pub rarity: Rarity,
+}
+
+impl Arcane {
+    pub fn rank(&self) -> u32 {
+        self.rank.unwrap_or(self.max_rank).min(self.max_rank)
+    }
+
+    pub fn with_rank(mut self, rank: u32) -> Self {
+        self.rank = Some(rank.min(self.max_rank));
+        self
+    }
+
+    pub fn fits(&self, weapon: &Weapon) -> bool {
+        self.slot == ArcaneSlot::Weapon && self.compatibility.allows(weapon)
+    }
+
+    /// The arcane as a mod that drains no capacity, so its stats go through
+    /// `Hit` and `Stacks` like any other mod's.
+    pub fn to_mod(&self) -> Mod {
+        Mod {
+            name: self.name.clone(),
+            stats: self.stats.clone(),
+            ranks: self.ranks.clone(),
+            max_rank: self.max_rank,
+            rank: self.rank,
+            rarity: self.rarity,
+            base_drain: 0,
+            polarity: None,
+            family: None,
+            compatibility: self.compatibility.clone(),
+            exilus: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ArcaneLibrary {
+    arcanes: HashMap<String, Arcane>,
+}
+
+impl ArcaneLibrary {
+    pub fn load(path: &Path) -> Self {
+        Self::try_load(path).expect("Should load arcane library")
+    }
+
+    /// Like `load`, but returns an error instead of panicking if the file
+    /// cannot be read or deserialized.
+    pub fn try_load(path: &Path) -> Result<Self, String> {
+        let file_content = std::fs::read_to_string(path)
+            .map_err(|error| format!("Cannot read {}: {error}", path.display()))?;
+        let arcanes = ron::from_str::<Vec<Arcane>>(file_content.as_str())
+            .map_err(|error| format!("Cannot deserialize {}: {error}", path.display()))?;
+        Ok(Self {
+            arcanes: arcanes.into_iter().map(|a| (a.name.clone(), a)).collect(),
+        })
+    }
+
+    /// Builds a library from the Public Export's arcanes, skipping relics,
+    /// along with the level stats of each arcane that could not be converted.
+    /// Unsupported arcanes are skipped and reported with all their level
+    /// stats.
+    pub fn from_export(items: &[RelicArcane]) -> (Self, Vec<(String, Vec<String>)>) {
+        let mut unparsed = vec![];
+        let mut arcanes = HashMap::new();
+
+        for item in items.iter().filter(|item| !item.is_relic()) {
+            let Some((arcane, item_unparsed)) = convert::convert_arcane(item) else {
+                let mut lines = item
+                    .level_stats
+                    .iter()
+                    .flat_map(|level| level.stats.iter().flat_map(|stats| stats.lines()))
+                    .map(|line| line.trim().to_owned())
+                    .filter(|line| !line.is_empty())
+                    .collect::<Vec<_>>();
+                lines.dedup();
+                unparsed.push((item.name.clone(), lines));
+                continue;
+            };
+            if !item_unparsed.is_empty() {
+                unparsed.push((item.name.clone(), item_unparsed));
+            }
+            arcanes.insert(arcane.name.clone(), arcane);
+        }
+
+        (Self { arcanes }, unparsed)
+    }
+
+    pub fn get(&self, arcane_name: &str) -> Option<&Arcane> {
+        self.arcanes.get(arcane_name)
+    }
+
+    /// Every arcane in the library, sorted by name.
+    pub fn arcanes(&self) -> Vec<&Arcane> {
+        let mut arcanes = self.arcanes.values().collect::<Vec<_>>();
+        arcanes.sort_by(|l, r| l.name.cmp(&r.name));
+        arcanes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        build::Loadout,
+        enemy::{Enemy, Faction},
+        mods::Trigger,
+        public_export::relic_arcane::ExportRelicArcane,
+        stacking::Stacks,
+        weapon::WeaponLibrary,
+    };
+
+    use assert_float_eq::assert_f32_near;
+
+    #[test]
+    fn weapon_arcane() {
+        let arcanes = ArcaneLibrary::load(Path::new("data/arcanes.ron"));
+        let weapons = WeaponLibrary::load(Path::new("data/weapons.ron"));
+        let braton_prime = weapons
+            .get("Braton Prime")
+            .expect("Braton Prime should exist");
+        let merciless = arcanes
+            .get("Primary Merciless")
+            .expect("Primary Merciless should exist");
+        let deadhead = arcanes
+            .get("Secondary Deadhead")
+            .expect("Secondary Deadhead should exist");
+
+        let mut loadout = Loadout::new(braton_prime.clone());
+        assert!(loadout.equip_arcane(deadhead.clone()).is_err());
+        for name in ["Melee Influence", "Arcane Energize"] {
+            let arcane = arcanes.get(name).expect("Arcane should exist");
+            assert!(!arcane.fits(braton_prime));
+        }
+        let influence = arcanes
+            .get("Melee Influence")
+            .expect("Melee Influence should exist");
+        assert_eq!(influence.compatibility, Compatibility::Melee);
+        let energize = arcanes
+            .get("Arcane Energize")
+            .expect("Arcane Energize should exist");
+        assert_eq!(energize.slot, ArcaneSlot::Warframe);
+        loadout
+            .equip_arcane(merciless.clone().with_rank(2))
+            .expect("Primary Merciless should fit Braton Prime");
+
+        let enemy = Enemy::new(Faction::Grineer);
+        let hit = loadout
+            .hit("Normal Attack", enemy)
+            .expect("Braton Prime should have a normal attack");
+        let base = hit.total_quantized();
+        let stacked = hit.clone().with_stacks(Stacks::max(hit.mods()));
+        // 12 stacks of +15% damage at rank 2.
+        assert_f32_near!(stacked.total_quantized(), base * 2.8, 8);
+    }
+
+    #[test]
+    fn export_arcanes() {
+        let json = std::fs::read_to_string("data/public_export/ExportRelicArcane_en.json")
+            .expect("Should read sample");
+        let mut export =
+            serde_json::from_str::<ExportRelicArcane>(&json).expect("Should deserialize sample");
+        let (arcanes, unparsed) = ArcaneLibrary::from_export(&export.export_relic_arcane);
+
+        assert_eq!(arcanes.arcanes().len(), 2);
+        let deadhead = arcanes
+            .get("Primary Deadhead")
+            .expect("Primary Deadhead should exist");
+        assert_eq!(deadhead.slot, ArcaneSlot::Weapon);
+        assert_eq!(deadhead.compatibility, Compatibility::Primary);
+        assert_eq!(deadhead.max_rank, 5);
+        assert_eq!(deadhead.stats.len(), 1);
+        assert_eq!(deadhead.stats[0].trigger, Some(Trigger::WeakpointKill));
+        assert_eq!(deadhead.stats[0].stacking.as_ref().map(|s| s.max), Some(1));
+        assert_eq!(deadhead.stats[0].effect, ModEffect::Damage(1.8));
+        assert_eq!(
+            deadhead.clone().with_rank(2).to_mod().ranked_stats()[0].effect,
+            ModEffect::Damage(0.9)
+        );
+
+        let energize = arcanes
+            .get("Arcane Energize")
+            .expect("Arcane Energize should exist");
+        assert_eq!(energize.slot, ArcaneSlot::Warframe);
+        assert_eq!(unparsed.len(), 1);
+
+        // Operator arcanes have no loadout to go on.
+        for item in export.export_relic_arcane.iter_mut() {
+            if item.name == "Arcane Energize" {
+                item.name = "Magus Elevate".to_owned();
+            }
+        }
+        let (arcanes, unparsed) = ArcaneLibrary::from_export(&export.export_relic_arcane);
+        assert_eq!(arcanes.arcanes().len(), 1);
+        assert!(unparsed.iter().any(|(name, _)| name == "Magus Elevate"));
+    }
+}
@@ -1,3 +1,425 @@
-fn main() {
-    println!("Hello, world!");
+use wfcalc::{
+    arcane::ArcaneLibrary,
+    buff::BuffLibrary,
+    build::{Loadout, MOD_SLOTS, SlotId},
+    enemy::{EnemyLibrary, Pool},
+    hit::Hit,
+    mods::ModLibrary,
+    public_export::{
+        cache::Cache, relic_arcane::ExportRelicArcane, upgrade::ExportUpgrades,
+        weapon::ExportWeapons,
+    },
+    simulation::{self, TimeToKill},
+    stacking::Stacks,
+    weapon::WeaponLibrary,
+};
+
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use serde_json::json;
+
+const USAGE: &str = "\
+Usage: main <weapon> [options]
+
+Options:
+    --attack <name>     Attack to use, defaults to the weapon's first one
+    --mod <name[@rank]> Mod to equip, in slot order, at max rank by default
+    --exilus <name[@rank]>
+                        Exilus mod to equip, at max rank by default
+    --arcane <name[@rank]>
+                        Arcane to equip, at max rank by default
+    --buff <name[@strength]>
+                        Buff to fight under, at the given ability strength
+                        in percent, 100 by default
+    --enemy <name>      Enemy preset, e.g. \"Lvl 200 Corrupted Heavy Gunner\",
+                        defaults to Heavy Gunner
+    --level <level>     Enemy level, defaults to the level in --enemy or the
+                        preset's base level
+    --runs <count>      Time to kill simulation runs, defaults to 1000
+    --seed <seed>       Time to kill simulation seed, defaults to 0
+    --data <dir>        Directory with mods.ron, arcanes.ron, buffs.ron,
+                        weapons.ron and enemies.ron
+    --export <dir>      Take weapons, mods and arcanes from a Public Export
+                        cache synced by public_export instead of --data
+    --json              Print JSON instead of a table
+
+Conditional stats of mods and arcanes are assumed to be at their maximum
+stacks.";
+
+struct Args {
+    weapon: String,
+    attack: Option<String>,
+    mods: Vec<(String, Option<u32>)>,
+    exilus: Option<(String, Option<u32>)>,
+    arcane: Option<(String, Option<u32>)>,
+    buffs: Vec<(String, Option<u32>)>,
+    enemy: String,
+    level: Option<u32>,
+    runs: usize,
+    seed: u64,
+    data: PathBuf,
+    export: Option<PathBuf>,
+    json: bool,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut weapon = None;
+    let mut parsed = Args {
+        weapon: String::new(),
+        attack: None,
+        mods: vec![],
+        exilus: None,
+        arcane: None,
+        buffs: vec![],
+        enemy: "Heavy Gunner".to_owned(),
+        level: None,
+        runs: 1000,
+        seed: 0,
+        data: PathBuf::from("data"),
+        export: None,
+        json: false,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {arg}"));
+        match arg.as_str() {
+            "--attack" => parsed.attack = Some(value()?),
+            "--mod" => parsed.mods.push(parse_ranked(value()?)?),
+            "--exilus" => parsed.exilus = Some(parse_ranked(value()?)?),
+            "--arcane" => parsed.arcane = Some(parse_ranked(value()?)?),
+            "--buff" => parsed.buffs.push(parse_ranked(value()?)?),
+            "--enemy" => parsed.enemy = value()?,
+            "--level" => {
+                let level = value()?;
+                parsed.level = Some(
+                    level
+                        .parse()
+                        .map_err(|_| format!("Invalid level {level}"))?,
+                );
+            }
+            "--runs" => {
+                let runs = value()?;
+                parsed.runs = runs.parse().map_err(|_| format!("Invalid runs {runs}"))?;
+            }
+            "--seed" => {
+                let seed = value()?;
+                parsed.seed = seed.parse().map_err(|_| format!("Invalid seed {seed}"))?;
+            }
+            "--data" => parsed.data = PathBuf::from(value()?),
+            "--export" => parsed.export = Some(PathBuf::from(value()?)),
+            "--json" => parsed.json = true,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {arg}")),
+            _ if weapon.is_none() => weapon = Some(arg),
+            _ => return Err(format!("Unexpected argument {arg}")),
+        }
+    }
+
+    parsed.weapon = weapon.ok_or("Missing weapon")?;
+    Ok(parsed)
+}
+
+/// Parses `name[@rank]`, or `name[@strength]` for buffs.
+fn parse_ranked(value: String) -> Result<(String, Option<u32>), String> {
+    Ok(match value.rsplit_once('@') {
+        Some((name, rank)) => (
+            name.to_owned(),
+            Some(rank.parse().map_err(|_| format!("Invalid value {rank}"))?),
+        ),
+        None => (value, None),
+    })
+}
+
+struct Report {
+    loadout: Loadout,
+    hit: Hit,
+    time_to_kill: Option<TimeToKill>,
+}
+
+/// Weapons, mods and arcanes from the Public Export cache at `dir`.
+fn load_export(
+    dir: &Path,
+    data: &Path,
+) -> Result<(WeaponLibrary, ModLibrary, ArcaneLibrary), String> {
+    let cache = Cache::new(dir);
+    let weapons = cache.load_export::<ExportWeapons>("Weapons")?;
+    let upgrades = cache.load_export::<ExportUpgrades>("Upgrades")?;
+    let arcanes = cache.load_export::<ExportRelicArcane>("RelicArcane")?;
+
+    let (mods, _) = ModLibrary::from_export(&data.join("mods.ron"), &upgrades.export_upgrades);
+    let (arcanes, _) = ArcaneLibrary::from_export(&arcanes.export_relic_arcane);
+    Ok((
+        WeaponLibrary::from_export(&weapons.export_weapons),
+        mods,
+        arcanes,
+    ))
+}
+
+fn calculate(args: &Args) -> Result<Report, String> {
+    let (weapons, mods, arcanes) = match &args.export {
+        Some(dir) => load_export(dir, &args.data)?,
+        None => (
+            WeaponLibrary::try_load(&args.data.join("weapons.ron"))?,
+            ModLibrary::try_load(&args.data.join("mods.ron"))?,
+            ArcaneLibrary::try_load(&args.data.join("arcanes.ron"))?,
+        ),
+    };
+    let buffs = BuffLibrary::try_load(&args.data.join("buffs.ron"))?;
+    let enemies = EnemyLibrary::try_load(&args.data.join("enemies.ron"))?;
+
+    let weapon = weapons
+        .get(&args.weapon)
+        .ok_or(format!("Unknown weapon {}", args.weapon))?;
+    let mut enemy = enemies
+        .parse(&args.enemy)
+        .ok_or(format!("Unknown enemy {}", args.enemy))?;
+    if let Some(level) = args.level {
+        enemy.level = level;
+    }
+
+    if args.mods.len() > MOD_SLOTS {
+        return Err(format!("At most {MOD_SLOTS} mods can be equipped"));
+    }
+    let mut loadout = Loadout::new(weapon.clone());
+    let slots = args
+        .mods
+        .iter()
+        .enumerate()
+        .map(|(slot, r#mod)| (SlotId::Mod(slot), r#mod))
+        .chain(args.exilus.iter().map(|r#mod| (SlotId::Exilus, r#mod)));
+    for (slot, (name, rank)) in slots {
+        let r#mod = mods.get(name).ok_or(format!("Unknown mod {name}"))?.clone();
+        let r#mod = match rank {
+            Some(rank) => r#mod.with_rank(*rank),
+            None => r#mod,
+        };
+        loadout
+            .equip(slot, r#mod)
+            .map_err(|error| format!("Cannot equip {name}: {error:?}"))?;
+    }
+    if let Some((name, rank)) = &args.arcane {
+        let arcane = arcanes
+            .get(name)
+            .ok_or(format!("Unknown arcane {name}"))?
+            .clone();
+        let arcane = match rank {
+            Some(rank) => arcane.with_rank(*rank),
+            None => arcane,
+        };
+        loadout
+            .equip_arcane(arcane)
+            .map_err(|error| format!("Cannot equip {name}: {error:?}"))?;
+    }
+    for (name, strength) in args.buffs.iter() {
+        let buff = buffs
+            .get(name)
+            .ok_or(format!("Unknown buff {name}"))?
+            .clone();
+        let strength = strength
+            .map(|strength| strength as f32 / 100.)
+            .unwrap_or(1.);
+        loadout.buffs.push(buff.with_strength(strength));
+    }
+
+    let attack = match &args.attack {
+        Some(attack) => attack.clone(),
+        None => weapon
+            .attacks
+            .first()
+            .ok_or(format!("{} has no attacks", weapon.name))?
+            .name
+            .clone(),
+    };
+    // Conditional mods are assumed to be at their maximum stacks.
+    let hit = loadout
+        .hit(&attack, enemy)
+        .ok_or(format!("Unknown attack {attack}"))?;
+    let hit = hit.clone().with_stacks(Stacks::max(hit.mods()));
+    let time_to_kill = simulation::time_to_kill(&hit, args.runs, args.seed);
+
+    Ok(Report {
+        loadout,
+        hit,
+        time_to_kill,
+    })
+}
+
+fn print_table(report: &Report) {
+    let Report {
+        loadout,
+        hit,
+        time_to_kill,
+    } = report;
+    let enemy = hit.enemy();
+    let critical = hit.critical();
+    let status_chance = hit.status_chance();
+
+    println!(
+        "{} - {} vs {} (level {})",
+        loadout.weapon.name,
+        hit.attack().name,
+        enemy.name,
+        enemy.level
+    );
+    for r#mod in loadout.mods() {
+        println!(
+            "  {} (rank {}/{})",
+            r#mod.name,
+            r#mod.rank(),
+            r#mod.max_rank
+        );
+    }
+    if let Some(arcane) = &loadout.arcane {
+        println!(
+            "  {} (arcane, rank {}/{})",
+            arcane.name,
+            arcane.rank(),
+            arcane.max_rank
+        );
+    }
+    for buff in loadout.buffs.iter() {
+        println!("  {} (buff)", buff.name);
+    }
+    let over = if loadout.validate().is_err() {
+        " (over capacity)"
+    } else {
+        ""
+    };
+    println!(
+        "  Capacity {}/{}{over}",
+        loadout.drain(),
+        loadout.capacity()
+    );
+
+    println!();
+    println!("{:<16}{:>12}{:>10}", "Damage type", "Per hit", "Status");
+    let mut contributions = hit.contributions().into_iter().collect::<Vec<_>>();
+    contributions.sort_by_key(|(damage_type, _)| damage_type.to_string());
+    for (damage_type, damage) in contributions {
+        let chance = status_chance.get(&damage_type).copied().unwrap_or(0.);
+        println!(
+            "{:<16}{:>12.2}{:>9.2}%",
+            damage_type.to_string(),
+            damage,
+            chance * 100.
+        );
+    }
+    println!("{:<16}{:>12.2}", "Total", hit.total_quantized());
+    println!("{:<16}{:>12.2}", "To health", hit.damage_to(Pool::Health));
+    println!("{:<16}{:>12.2}", "To shield", hit.damage_to(Pool::Shield));
+
+    println!();
+    println!("Critical chance      {:>10.2}%", critical.chance * 100.);
+    println!("Critical multiplier  {:>10.2}x", critical.multiplier);
+    println!(
+        "Status chance        {:>10.2}%",
+        hit.total_status_chance() * 100.
+    );
+    println!("Procs per shot       {:>10.2}", hit.procs_per_shot());
+    println!("Multishot            {:>10.2}", hit.multishot());
+
+    println!();
+    println!("Burst DPS            {:>10.2}", hit.burst_dps());
+    println!("Sustained DPS        {:>10.2}", hit.sustained_dps());
+    println!("Status DPS           {:>10.2}", hit.status_dps());
+
+    println!();
+    match time_to_kill {
+        Some(ttk) => {
+            println!("TTK mean             {:>10.2}s", ttk.mean);
+            println!("TTK median           {:>10.2}s", ttk.median);
+            println!("TTK 95th percentile  {:>10.2}s", ttk.p95);
+            println!("Kills                {:>10}/{}", ttk.kills, ttk.runs);
+        }
+        None => println!("TTK                  never kills"),
+    }
+}
+
+fn print_json(report: &Report) {
+    let Report {
+        loadout,
+        hit,
+        time_to_kill,
+    } = report;
+    let critical = hit.critical();
+    let status_chance = hit.status_chance();
+
+    let damage = hit
+        .contributions()
+        .into_iter()
+        .map(|(damage_type, damage)| {
+            (
+                damage_type.to_string(),
+                json!({
+                    "damage": damage,
+                    "status_chance": status_chance.get(&damage_type).copied().unwrap_or(0.),
+                }),
+            )
+        })
+        .collect::<serde_json::Map<_, _>>();
+
+    let output = json!({
+        "weapon": loadout.weapon.name,
+        "attack": hit.attack().name,
+        "enemy": { "name": hit.enemy().name, "level": hit.enemy().level },
+        "mods": loadout
+            .mods()
+            .iter()
+            .map(|m| json!({ "name": m.name, "rank": m.rank() }))
+            .collect::<Vec<_>>(),
+        "arcane": loadout
+            .arcane
+            .as_ref()
+            .map(|a| json!({ "name": a.name, "rank": a.rank() })),
+        "buffs": loadout.buffs.iter().map(|b| &b.name).collect::<Vec<_>>(),
+        "drain": loadout.drain(),
+        "capacity": loadout.capacity(),
+        "damage": damage,
+        "total": hit.total_quantized(),
+        "to_health": hit.damage_to(Pool::Health),
+        "to_shield": hit.damage_to(Pool::Shield),
+        "critical_chance": critical.chance,
+        "critical_multiplier": critical.multiplier,
+        "status_chance": hit.total_status_chance(),
+        "procs_per_shot": hit.procs_per_shot(),
+        "multishot": hit.multishot(),
+        "burst_dps": hit.burst_dps(),
+        "sustained_dps": hit.sustained_dps(),
+        "status_dps": hit.status_dps(),
+        "time_to_kill": time_to_kill.as_ref().map(|ttk| json!({
+            "runs": ttk.runs,
+            "kills": ttk.kills,
+            "mean": ttk.mean,
+            "median": ttk.median,
+            "p95": ttk.p95,
+        })),
+    });
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&output).expect("Report should be serializable")
+    );
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{error}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match calculate(&args) {
+        Ok(report) if args.json => print_json(&report),
+        Ok(report) => print_table(&report),
+        Err(error) => {
+            eprintln!("{error}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    ExitCode::SUCCESS
 }
@@ -1,55 +1,128 @@
-use std::{
-    collections::HashMap,
-    io::Read,
-    process::{Command, ExitStatus},
-};
-
-fn main() -> std::io::Result<()> {
-    let compressed_index =
-        reqwest::blocking::get("https://origin.warframe.com/PublicExport/index_en.txt.lzma")
-            .expect("Public Export index request should succeed")
-            .bytes()
-            .expect("Public Export index request should have a body")
-            .to_vec();
-
-    std::fs::write("/tmp/index_en.txt.lzma", &compressed_index).unwrap();
-
-    assert!(
-        Command::new("xz")
-            .args(["-df", "/tmp/index_en.txt.lzma"])
-            .spawn()
-            .expect("xz command should be usable")
-            .wait()
-            .expect("child should exit")
-            .success(),
-        "xz command failed to run"
-    );
+use wfcalc::public_export::cache::{self, Cache};
+
+use std::{path::PathBuf, process::ExitCode};
+
+const USAGE: &str = "\
+Usage: public_export [options]
+
+Mirrors every Export* manifest of the Public Export into a local cache,
+downloading only the manifests whose hash changed since the last sync.
+
+Options:
+    --base-url <url>    Serve both the index and the manifests from <url>
+    --cache <dir>       Cache directory, defaults to cache/public_export
+    --lang <lang>       Language of the manifests, defaults to en";
+
+const INDEX_URL: &str = "https://origin.warframe.com";
+const CONTENT_URL: &str = "http://content.warframe.com";
 
-    let index_file =
-        std::fs::read_to_string("/tmp/index_en.txt").expect("index.txt should be writable");
-
-    let index = index_file
-        .lines()
-        .map(|line| {
-            let category = line
-                .chars()
-                .skip("Export".len())
-                .take_while(|c| *c != '_')
-                .collect::<String>();
-            (category, line)
-        })
-        .collect::<HashMap<_, _>>();
-
-    for (category, line) in index.into_iter() {
-        let url = format!("http://content.warframe.com/PublicExport/Manifest/{line}");
-        let json = reqwest::blocking::get(url.as_str())
-            .expect(format!("Category '{category}' should be accessible at '{url}'").as_str())
-            .json();
+struct Args {
+    index_url: String,
+    content_url: String,
+    cache: PathBuf,
+    lang: String,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args {
+        index_url: INDEX_URL.to_owned(),
+        content_url: CONTENT_URL.to_owned(),
+        cache: PathBuf::from("cache/public_export"),
+        lang: "en".to_owned(),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {arg}"));
+        match arg.as_str() {
+            "--base-url" => {
+                let url = value()?.trim_end_matches('/').to_owned();
+                parsed.index_url = url.clone();
+                parsed.content_url = url;
+            }
+            "--cache" => parsed.cache = PathBuf::from(value()?),
+            "--lang" => parsed.lang = value()?,
+            _ => return Err(format!("Unexpected argument {arg}")),
+        }
     }
 
-    Ok(())
+    Ok(parsed)
+}
+
+fn fetch(url: &str) -> Result<Vec<u8>, String> {
+    reqwest::blocking::get(url)
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.bytes())
+        .map(|bytes| bytes.to_vec())
+        .map_err(|error| format!("Request to {url} failed: {error}"))
 }
 
-fn upgrades() {
-    Command::new("cat");
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{error}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let cache = Cache::new(&args.cache);
+
+    let index_url = format!(
+        "{}/PublicExport/index_{}.txt.lzma",
+        args.index_url, args.lang
+    );
+    let index = match fetch(&index_url).and_then(|compressed| {
+        cache::decompress_index(&compressed)
+            .map_err(|error| format!("Index decompression failed: {error}"))
+    }) {
+        Ok(index) => index,
+        Err(error) => {
+            eprintln!("{error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let manifests = cache::parse_index(&index);
+    let (mut downloaded, mut unchanged, mut failed) = (0, 0, 0);
+
+    for manifest in manifests.iter() {
+        if cache.contains(manifest) {
+            unchanged += 1;
+            continue;
+        }
+
+        let url = format!(
+            "{}/PublicExport/Manifest/{}",
+            args.content_url,
+            manifest.line()
+        );
+        match fetch(&url).and_then(|content| {
+            cache
+                .store(manifest, &content)
+                .map_err(|error| format!("Writing {} failed: {error}", manifest.file_name))
+        }) {
+            Ok(()) => {
+                println!("Downloaded {}", manifest.file_name);
+                downloaded += 1;
+            }
+            Err(error) => {
+                eprintln!("{error}");
+                failed += 1;
+            }
+        }
+    }
+
+    println!("{downloaded} downloaded, {unchanged} unchanged, {failed} failed");
+
+    // The index only moves forward once every manifest it lists is cached, so
+    // an interrupted sync resumes where it stopped and readers never see a
+    // half-synced export.
+    if failed > 0 {
+        return ExitCode::FAILURE;
+    }
+    if let Err(error) = cache.save_index(&manifests) {
+        eprintln!("Writing the index failed: {error}");
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
 }
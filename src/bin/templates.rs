@@ -20,6 +20,7 @@ fn main() {
                 effect: ModEffect::Multishot(0.3),
             },
         ],
+        ..Default::default()
     };
 
     let text = ron::ser::to_string_pretty(&galvanized_chamber, ron::ser::PrettyConfig::default())
@@ -0,0 +1,149 @@
+use crate::{damage::DamageType, mods::Compatibility};
+
+use std::{collections::HashMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+/// Where a buff applies in the damage formula.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub enum BuffEffect {
+    /// Added to base damage mods such as Serration, e.g. Vex Armor.
+    BaseDamage(f32),
+    /// Multiplies damage like faction mods, e.g. Roar and Eclipse. Buffs of
+    /// this kind add up with each other.
+    DamageMultiplier(f32),
+    /// Extra damage of a type, as a share of the hit's modded damage, e.g.
+    /// Xata's Whisper.
+    AddedDamage(DamageType, f32),
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub enum BuffSource {
+    /// A Warframe ability, scaled by its caster's ability strength, 1 meaning
+    /// 100%.
+    Ability {
+        strength: f32,
+    },
+    Aura,
+}
+
+/// A damage buff from outside the weapon.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct Buff {
+    pub name: String,
+    /// Effect at 100% ability strength.
+    pub effect: BuffEffect,
+    pub source: BuffSource,
+    /// Weapons the buff applies to.
+    #[serde(default)]
+    pub compatibility: Compatibility,
+}
+
+impl Buff {
+    pub fn with_strength(mut self, strength: f32) -> Self {
+        if let BuffSource::Ability { .. } = self.source {
+            self.source = BuffSource::Ability { strength };
+        }
+        self
+    }
+
+    /// Effect once the caster's ability strength is applied.
+    pub fn scaled_effect(&self) -> BuffEffect {
+        let strength = match self.source {
+            BuffSource::Ability { strength } => strength,
+            BuffSource::Aura => 1.,
+        };
+        match &self.effect {
+            BuffEffect::BaseDamage(value) => BuffEffect::BaseDamage(value * strength),
+            BuffEffect::DamageMultiplier(value) => BuffEffect::DamageMultiplier(value * strength),
+            BuffEffect::AddedDamage(damage_type, value) => {
+                BuffEffect::AddedDamage(*damage_type, value * strength)
+            }
+        }
+    }
+}
+
+pub struct BuffLibrary {
+    buffs: HashMap<String, Buff>,
+}
+
+impl BuffLibrary {
+    pub fn load(path: &Path) -> Self {
+        Self::try_load(path).expect("Should load buff library")
+    }
+
+    /// Like `load`, but returns an error instead of panicking if the file
+    /// cannot be read or deserialized.
+    pub fn try_load(path: &Path) -> Result<Self, String> {
+        let file_content = std::fs::read_to_string(path)
+            .map_err(|error| format!("Cannot read {}: {error}", path.display()))?;
+        let buffs = ron::from_str::<Vec<Buff>>(file_content.as_str())
+            .map_err(|error| format!("Cannot deserialize {}: {error}", path.display()))?;
+        Ok(Self {
+            buffs: buffs.into_iter().map(|b| (b.name.clone(), b)).collect(),
+        })
+    }
+
+    pub fn get(&self, buff_name: &str) -> Option<&Buff> {
+        self.buffs.get(buff_name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        build::Loadout,
+        damage::Special,
+        enemy::{Enemy, Faction},
+        weapon::WeaponLibrary,
+    };
+
+    use assert_float_eq::assert_f32_near;
+
+    #[test]
+    fn buffs() {
+        let buffs = BuffLibrary::load(Path::new("data/buffs.ron"));
+        let weapons = WeaponLibrary::load(Path::new("data/weapons.ron"));
+        let buff = |name: &str| buffs.get(name).expect("Buff should exist").clone();
+        let loadout = Loadout::new(
+            weapons
+                .get("Braton Prime")
+                .expect("Braton Prime should exist")
+                .clone(),
+        );
+        let total = |loadout: &Loadout| {
+            loadout
+                .hit("Normal Attack", Enemy::new(Faction::Grineer))
+                .expect("Braton Prime should have a normal attack")
+                .total_quantized()
+        };
+        let base = total(&loadout);
+
+        let mut roar = loadout.clone();
+        roar.buffs = vec![buff("Roar").with_strength(2.)];
+        assert_f32_near!(total(&roar), base * 2., 8);
+
+        // Buffs of the same kind add up, different kinds multiply.
+        let mut stacked = roar.clone();
+        stacked.buffs.push(buff("Eclipse"));
+        stacked.buffs.push(buff("Vex Armor"));
+        assert_f32_near!(total(&stacked), base * 4. * 3.75, 8);
+
+        let mut xata = loadout.clone();
+        xata.buffs = vec![buff("Xata's Whisper")];
+        let hit = xata
+            .hit("Normal Attack", Enemy::new(Faction::Grineer))
+            .expect("Braton Prime should have a normal attack");
+        assert_f32_near!(
+            hit.contributions()[&DamageType::Special(Special::Void)],
+            base * 0.26,
+            8
+        );
+
+        // Steel Charge only applies to melee weapons.
+        let mut steel_charge = loadout.clone();
+        steel_charge.buffs = vec![buff("Steel Charge")];
+        assert_f32_near!(total(&steel_charge), base);
+    }
+}
@@ -0,0 +1,399 @@
+use crate::{
+    arcane::Arcane,
+    buff::Buff,
+    enemy::Enemy,
+    hit::Hit,
+    mods::{Mod, Polarity},
+    weapon::Weapon,
+};
+
+pub const MOD_SLOTS: usize = 8;
+pub const MAX_RANK: u32 = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SlotId {
+    Mod(usize),
+    Exilus,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Slot {
+    pub r#mod: Option<Mod>,
+    pub polarity: Option<Polarity>,
+}
+
+impl Slot {
+    /// Capacity drained by the slot's mod. A matching polarity halves the
+    /// drain and a mismatched one increases it by a quarter, rounding up.
+    pub fn drain(&self) -> u32 {
+        let Some(r#mod) = &self.r#mod else {
+            return 0;
+        };
+        let drain = r#mod.drain();
+
+        match (self.polarity, r#mod.polarity) {
+            (None, _) => drain,
+            (Some(slot), Some(polarity)) if slot == polarity => drain.div_ceil(2),
+            (Some(_), _) => (drain * 5).div_ceil(4),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadoutError {
+    InvalidSlot(SlotId),
+    Incompatible(String),
+    /// The mod cannot go in the exilus slot.
+    NotExilus(String),
+    /// Every slot the item could go in is taken.
+    SlotsFull(String),
+    DuplicateMod(String),
+    SameFamily {
+        family: String,
+        equipped: String,
+    },
+    OverCapacity {
+        drain: u32,
+        capacity: u32,
+    },
+}
+
+/// A weapon with its equipped mods and arcane, slot polarities, rank, Orokin
+/// Catalyst and the buffs it fights under.
+#[derive(Debug, Clone)]
+pub struct Loadout {
+    pub weapon: Weapon,
+    pub slots: [Slot; MOD_SLOTS],
+    pub exilus: Slot,
+    pub arcane: Option<Arcane>,
+    pub buffs: Vec<Buff>,
+    rank: u32,
+    pub catalyst: bool,
+}
+
+impl Loadout {
+    pub fn new(weapon: Weapon) -> Self {
+        Self {
+            weapon,
+            slots: Default::default(),
+            exilus: Slot::default(),
+            arcane: None,
+            buffs: vec![],
+            rank: 30,
+            catalyst: false,
+        }
+    }
+
+    pub fn with_rank(mut self, rank: u32) -> Self {
+        self.rank = rank.min(MAX_RANK);
+        self
+    }
+
+    pub fn with_catalyst(mut self, catalyst: bool) -> Self {
+        self.catalyst = catalyst;
+        self
+    }
+
+    pub fn rank(&self) -> u32 {
+        self.rank
+    }
+
+    pub fn slot(&self, id: SlotId) -> Result<&Slot, LoadoutError> {
+        match id {
+            SlotId::Mod(index) => self.slots.get(index),
+            SlotId::Exilus => Some(&self.exilus),
+        }
+        .ok_or(LoadoutError::InvalidSlot(id))
+    }
+
+    fn slot_mut(&mut self, id: SlotId) -> Result<&mut Slot, LoadoutError> {
+        match id {
+            SlotId::Mod(index) => self.slots.get_mut(index),
+            SlotId::Exilus => Some(&mut self.exilus),
+        }
+        .ok_or(LoadoutError::InvalidSlot(id))
+    }
+
+    /// Every slot with its id, mod slots first.
+    fn all_slots(&self) -> impl Iterator<Item = (SlotId, &Slot)> {
+        Self::slot_ids().zip(self.slots.iter().chain(std::iter::once(&self.exilus)))
+    }
+
+    /// Every slot, mod slots first.
+    pub fn slot_ids() -> impl Iterator<Item = SlotId> {
+        (0..MOD_SLOTS)
+            .map(SlotId::Mod)
+            .chain(std::iter::once(SlotId::Exilus))
+    }
+
+    pub fn set_polarity(
+        &mut self,
+        id: SlotId,
+        polarity: Option<Polarity>,
+    ) -> Result<(), LoadoutError> {
+        self.slot_mut(id)?.polarity = polarity;
+        Ok(())
+    }
+
+    /// Equips a mod, replacing the one in the slot. Duplicates, mods from the
+    /// same family as another equipped mod and non-exilus mods in the exilus
+    /// slot are rejected.
+    pub fn equip(&mut self, id: SlotId, r#mod: Mod) -> Result<(), LoadoutError> {
+        self.slot(id)?;
+        if !r#mod.compatibility.allows(&self.weapon) {
+            return Err(LoadoutError::Incompatible(r#mod.name));
+        }
+        if id == SlotId::Exilus && !r#mod.exilus {
+            return Err(LoadoutError::NotExilus(r#mod.name));
+        }
+        for (_, slot) in self.all_slots().filter(|(other, _)| *other != id) {
+            let Some(equipped) = &slot.r#mod else {
+                continue;
+            };
+            if equipped.name == r#mod.name {
+                return Err(LoadoutError::DuplicateMod(r#mod.name));
+            }
+            if let Some(family) = &r#mod.family
+                && equipped.family.as_ref() == Some(family)
+            {
+                return Err(LoadoutError::SameFamily {
+                    family: family.clone(),
+                    equipped: equipped.name.clone(),
+                });
+            }
+        }
+
+        self.slot_mut(id)?.r#mod = Some(r#mod);
+        Ok(())
+    }
+
+    pub fn unequip(&mut self, id: SlotId) -> Result<Option<Mod>, LoadoutError> {
+        Ok(self.slot_mut(id)?.r#mod.take())
+    }
+
+    /// Equips an arcane, returning the one it replaces. Warframe arcanes and
+    /// arcanes of other weapon classes are rejected.
+    pub fn equip_arcane(&mut self, arcane: Arcane) -> Result<Option<Arcane>, LoadoutError> {
+        if !arcane.fits(&self.weapon) {
+            return Err(LoadoutError::Incompatible(arcane.name));
+        }
+        Ok(self.arcane.replace(arcane))
+    }
+
+    /// Equipped mods in slot order, exilus last, which is also the order their
+    /// elements combine in.
+    pub fn mods(&self) -> Vec<Mod> {
+        self.all_slots()
+            .filter_map(|(_, slot)| slot.r#mod.clone())
+            .collect()
+    }
+
+    pub fn capacity(&self) -> u32 {
+        if self.catalyst {
+            self.rank * 2
+        } else {
+            self.rank
+        }
+    }
+
+    pub fn drain(&self) -> u32 {
+        self.all_slots().map(|(_, slot)| slot.drain()).sum()
+    }
+
+    pub fn validate(&self) -> Result<(), LoadoutError> {
+        let (drain, capacity) = (self.drain(), self.capacity());
+        if drain > capacity {
+            return Err(LoadoutError::OverCapacity { drain, capacity });
+        }
+        Ok(())
+    }
+
+    /// Slots to re-polarize with Forma so the loadout fits its capacity,
+    /// fewest first, or `None` if no amount of Forma is enough.
+    pub fn forma_plan(&self) -> Option<Vec<(SlotId, Polarity)>> {
+        let mut loadout = self.clone();
+        let mut plan = vec![];
+
+        while loadout.drain() > loadout.capacity() {
+            let (id, polarity, _) = loadout
+                .all_slots()
+                .filter_map(|(id, slot)| {
+                    let polarity = slot.r#mod.as_ref()?.polarity?;
+                    let polarized = Slot {
+                        r#mod: slot.r#mod.clone(),
+                        polarity: Some(polarity),
+                    };
+                    let saved = slot.drain().saturating_sub(polarized.drain());
+                    (saved > 0).then_some((id, polarity, saved))
+                })
+                .max_by_key(|(_, _, saved)| *saved)?;
+
+            loadout
+                .set_polarity(id, Some(polarity))
+                .expect("Should polarize an existing slot");
+            plan.push((id, polarity));
+        }
+
+        Some(plan)
+    }
+
+    /// A hit with the loadout's mods, followed by its arcane, and the buffs
+    /// that apply to the weapon.
+    pub fn hit(&self, attack: &str, enemy: Enemy) -> Option<Hit> {
+        let attack = self.weapon.attack(attack)?;
+        let mut mods = self.mods();
+        mods.extend(self.arcane.as_ref().map(Arcane::to_mod));
+        let buffs = self
+            .buffs
+            .iter()
+            .filter(|buff| buff.compatibility.allows(&self.weapon))
+            .cloned()
+            .collect();
+        Some(
+            Hit::new(attack, mods, enemy)
+                .with_progenitor(self.weapon.progenitor)
+                .with_buffs(buffs),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        mods::ModLibrary,
+        weapon::{PrimaryWeaponType, WeaponType},
+    };
+
+    use std::path::Path;
+
+    fn loadout() -> Loadout {
+        Loadout::new(Weapon {
+            name: "Braton".to_owned(),
+            mastery_rank: 0,
+            ty: WeaponType::Primary(PrimaryWeaponType::Rifle),
+            attacks: vec![],
+            progenitor: None,
+        })
+    }
+
+    fn get(name: &str) -> Mod {
+        ModLibrary::load(Path::new("data/mods.ron"))
+            .get(name)
+            .expect("Mod should exist")
+            .clone()
+    }
+
+    #[test]
+    fn drain_and_capacity() {
+        let mut loadout = loadout();
+        loadout
+            .equip(SlotId::Mod(0), get("Galvanized Chamber"))
+            .expect("Should equip");
+        loadout
+            .equip(SlotId::Mod(1), get("Cryo Rounds"))
+            .expect("Should equip");
+        assert_eq!(loadout.drain(), 19 + 9);
+
+        loadout
+            .set_polarity(SlotId::Mod(0), Some(Polarity::Madurai))
+            .expect("Should polarize");
+        loadout
+            .set_polarity(SlotId::Mod(1), Some(Polarity::Vazarin))
+            .expect("Should polarize");
+        assert_eq!(loadout.drain(), 10 + 12);
+
+        let loadout = loadout.with_rank(10);
+        assert_eq!(
+            loadout.validate(),
+            Err(LoadoutError::OverCapacity {
+                drain: 22,
+                capacity: 10
+            })
+        );
+        assert_eq!(
+            loadout.with_catalyst(true).validate(),
+            Err(LoadoutError::OverCapacity {
+                drain: 22,
+                capacity: 20
+            })
+        );
+    }
+
+    #[test]
+    fn duplicates_and_forma() {
+        let mut loadout = loadout();
+        loadout
+            .equip(SlotId::Mod(0), get("Galvanized Chamber"))
+            .expect("Should equip");
+        assert_eq!(
+            loadout.equip(SlotId::Mod(1), get("Galvanized Chamber")),
+            Err(LoadoutError::DuplicateMod("Galvanized Chamber".to_owned()))
+        );
+
+        let family = |name: &str| Mod {
+            name: name.to_owned(),
+            family: Some("Multishot".to_owned()),
+            ..Default::default()
+        };
+        loadout
+            .equip(SlotId::Mod(1), family("Split Chamber"))
+            .expect("Should equip");
+        assert_eq!(
+            loadout.equip(SlotId::Mod(2), family("Vigilante Armaments")),
+            Err(LoadoutError::SameFamily {
+                family: "Multishot".to_owned(),
+                equipped: "Split Chamber".to_owned()
+            })
+        );
+
+        loadout
+            .equip(SlotId::Mod(2), get("Cryo Rounds"))
+            .expect("Should equip");
+        loadout
+            .equip(SlotId::Mod(3), get("Malignant Force"))
+            .expect("Should equip");
+        let loadout = loadout.with_rank(25);
+        assert_eq!(loadout.drain(), 19 + 9 + 7);
+        assert_eq!(
+            loadout.forma_plan(),
+            Some(vec![
+                (SlotId::Mod(0), Polarity::Madurai),
+                (SlotId::Mod(2), Polarity::Madurai)
+            ])
+        );
+        assert_eq!(loadout.with_rank(5).forma_plan(), None);
+    }
+
+    #[test]
+    fn slot_checks() {
+        let mut loadout = loadout();
+        let invalid = SlotId::Mod(MOD_SLOTS);
+        assert_eq!(
+            loadout.equip(invalid, get("Cryo Rounds")),
+            Err(LoadoutError::InvalidSlot(invalid))
+        );
+        assert_eq!(
+            loadout.set_polarity(invalid, Some(Polarity::Madurai)),
+            Err(LoadoutError::InvalidSlot(invalid))
+        );
+        assert_eq!(
+            loadout.unequip(invalid),
+            Err(LoadoutError::InvalidSlot(invalid))
+        );
+
+        assert_eq!(
+            loadout.equip(SlotId::Exilus, get("Cryo Rounds")),
+            Err(LoadoutError::NotExilus("Cryo Rounds".to_owned()))
+        );
+        let vigilante_supplies = Mod {
+            name: "Vigilante Supplies".to_owned(),
+            exilus: true,
+            ..Default::default()
+        };
+        loadout
+            .equip(SlotId::Exilus, vigilante_supplies)
+            .expect("Should equip an exilus mod");
+        assert_eq!(loadout.mods().len(), 1);
+    }
+}
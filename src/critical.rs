@@ -0,0 +1,80 @@
+/// Critical stats of a single pellet once mods are applied.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Critical {
+    pub chance: f32,
+    pub multiplier: f32,
+}
+
+/// A possible critical tier of a hit: 0 is a normal hit, 1 a yellow crit, 2 an
+/// orange crit and 3 and above red crits.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CritTier {
+    pub tier: u32,
+    pub chance: f32,
+    pub damage: f32,
+}
+
+impl Critical {
+    /// Chance of landing each tier. Critical chance above 100% guarantees
+    /// `floor(chance)` tiers, with the remainder as the chance to reach the next.
+    pub fn tier_chances(&self) -> Vec<(u32, f32)> {
+        let chance = self.chance.max(0.);
+        let guaranteed = chance.floor();
+        let extra = chance - guaranteed;
+
+        if extra > 0. {
+            vec![
+                (guaranteed as u32, 1. - extra),
+                (guaranteed as u32 + 1, extra),
+            ]
+        } else {
+            vec![(guaranteed as u32, 1.)]
+        }
+    }
+
+    pub fn tier_multiplier(&self, tier: u32) -> f32 {
+        1. + tier as f32 * (self.multiplier - 1.)
+    }
+
+    pub fn average_multiplier(&self) -> f32 {
+        1. + self.chance.max(0.) * (self.multiplier - 1.)
+    }
+
+    pub fn tiers(&self, damage: f32) -> Vec<CritTier> {
+        self.tier_chances()
+            .into_iter()
+            .map(|(tier, chance)| CritTier {
+                tier,
+                chance,
+                damage: damage * self.tier_multiplier(tier),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_float_eq::assert_f32_near;
+
+    #[test]
+    fn crit_tiers() {
+        let critical = Critical {
+            chance: 1.4,
+            multiplier: 3.,
+        };
+
+        let tiers = critical.tiers(100.);
+        assert_eq!(tiers.len(), 2);
+
+        assert_eq!(tiers[0].tier, 1);
+        assert_f32_near!(tiers[0].chance, 0.6, 8);
+        assert_f32_near!(tiers[0].damage, 300.);
+
+        assert_eq!(tiers[1].tier, 2);
+        assert_f32_near!(tiers[1].chance, 0.4, 8);
+        assert_f32_near!(tiers[1].damage, 500.);
+
+        assert_f32_near!(critical.average_multiplier(), 3.8);
+    }
+}
@@ -1,6 +1,11 @@
-use serde::{Deserialize, Serialize};
+use std::{fmt, marker::PhantomData};
 
-#[derive(Debug, Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Copy)]
+use serde::{
+    Deserialize, Deserializer, Serialize,
+    de::{self, DeserializeOwned, IntoDeserializer, Visitor, value::StrDeserializer},
+};
+
+#[derive(Debug, Serialize, Hash, PartialEq, Eq, Clone, Copy)]
 #[serde(untagged)]
 pub enum DamageType {
     Physical(Ips),
@@ -15,13 +20,45 @@ pub enum Ips {
     Slash,
 }
 
-#[derive(Debug, Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, Serialize, Hash, PartialEq, Eq, Clone, Copy)]
 #[serde(untagged)]
 pub enum Element {
     Primary(PrimaryElement),
     Secondary(SecondaryElement),
 }
 
+impl fmt::Display for DamageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DamageType::Physical(ips) => write!(f, "{ips:?}"),
+            DamageType::Elemental(Element::Primary(element)) => write!(f, "{element:?}"),
+            DamageType::Elemental(Element::Secondary(element)) => write!(f, "{element:?}"),
+            DamageType::Special(special) => write!(f, "{special:?}"),
+        }
+    }
+}
+
+impl Element {
+    /// Every element, in the order the game lists them.
+    pub const ALL: [Element; 10] = {
+        use PrimaryElement::*;
+        use SecondaryElement::*;
+
+        [
+            Element::Primary(Heat),
+            Element::Primary(Cold),
+            Element::Primary(Electricity),
+            Element::Primary(Toxin),
+            Element::Secondary(Blast),
+            Element::Secondary(Radiation),
+            Element::Secondary(Gas),
+            Element::Secondary(Magnetic),
+            Element::Secondary(Viral),
+            Element::Secondary(Corrosive),
+        ]
+    };
+}
+
 #[derive(Debug, Deserialize, Serialize, Hash, PartialEq, Eq, Clone, Copy)]
 pub enum Special {
     Void,
@@ -45,4 +82,58 @@ pub enum SecondaryElement {
     Gas,
     Radiation,
     Corrosive,
-}
\ No newline at end of file
+}
+
+// `#[serde(untagged)]` buffers its input and cannot match bare identifiers such
+// as RON's `Cold`, so the nested enums are deserialized from their variant name.
+trait FromVariant: Sized {
+    fn from_variant(name: &str) -> Option<Self>;
+}
+
+fn variant<T: DeserializeOwned>(name: &str) -> Option<T> {
+    let deserializer: StrDeserializer<de::value::Error> = name.into_deserializer();
+    T::deserialize(deserializer).ok()
+}
+
+impl FromVariant for DamageType {
+    fn from_variant(name: &str) -> Option<Self> {
+        variant(name)
+            .map(DamageType::Physical)
+            .or_else(|| Element::from_variant(name).map(DamageType::Elemental))
+            .or_else(|| variant(name).map(DamageType::Special))
+    }
+}
+
+impl FromVariant for Element {
+    fn from_variant(name: &str) -> Option<Self> {
+        variant(name)
+            .map(Element::Primary)
+            .or_else(|| variant(name).map(Element::Secondary))
+    }
+}
+
+struct VariantVisitor<T>(PhantomData<T>);
+
+impl<T: FromVariant> Visitor<'_> for VariantVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a damage type variant name")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        T::from_variant(value).ok_or_else(|| E::custom(format!("unknown damage type `{value}`")))
+    }
+}
+
+impl<'de> Deserialize<'de> for DamageType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(VariantVisitor(PhantomData))
+    }
+}
+
+impl<'de> Deserialize<'de> for Element {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(VariantVisitor(PhantomData))
+    }
+}
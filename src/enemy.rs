@@ -1,6 +1,6 @@
-use crate::damage::DamageType;
+use crate::{damage::*, status::Debuffs};
 
-use std::collections::HashMap;
+use std::{collections::HashMap, path::Path};
 
 use serde::{Deserialize, Serialize};
 
@@ -13,13 +13,307 @@ pub enum Faction {
     Murmur,
 }
 
+impl Faction {
+    /// Damage type modifiers of the faction's health class.
+    pub fn health_modifier(&self, damage_type: &DamageType) -> f32 {
+        use DamageType::*;
+        use Element::*;
+        use PrimaryElement::*;
+        use SecondaryElement::*;
+
+        match (self, damage_type) {
+            (Faction::Grineer, Physical(Ips::Impact))
+            | (Faction::Grineer, Elemental(Secondary(Corrosive)))
+            | (Faction::Corpus, Physical(Ips::Puncture))
+            | (Faction::Corpus, Elemental(Secondary(Magnetic)))
+            | (Faction::Corrupted, Physical(Ips::Puncture))
+            | (Faction::Corrupted, Elemental(Secondary(Viral)))
+            | (Faction::Infested, Physical(Ips::Slash))
+            | (Faction::Infested, Elemental(Primary(Heat)))
+            | (Faction::Murmur, Elemental(Primary(Electricity)))
+            | (Faction::Murmur, Elemental(Secondary(Radiation))) => 1.5,
+            (Faction::Corrupted, Elemental(Secondary(Radiation)))
+            | (Faction::Murmur, Elemental(Secondary(Viral))) => 0.5,
+            _ => 1.,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
+pub enum Pool {
+    Overguard,
+    Shield,
+    Health,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Enemy {
+    #[serde(default)]
+    pub name: String,
     pub faction: Faction,
+    /// Unit-specific modifiers applied on top of the faction's health class.
+    #[serde(default)]
     pub weaknesses: HashMap<DamageType, f32>,
+    #[serde(default)]
+    pub shield_weaknesses: HashMap<DamageType, f32>,
+    pub base_level: u32,
+    pub level: u32,
+    /// Base pools at `base_level`.
+    pub health: f32,
+    pub shield: f32,
+    pub armor: f32,
+    pub overguard: f32,
+    /// Status debuffs currently applied to the enemy.
+    #[serde(skip)]
+    pub debuffs: Debuffs,
+}
+
+/// Enemy armor past this value no longer reduces damage, capping the
+/// reduction at 90%.
+const ARMOR_CAP: f32 = 2700.;
+
+/// Fraction of health damage removed by armor. Warframe armor is uncapped,
+/// while enemies clamp theirs to `ARMOR_CAP` first.
+pub fn armor_reduction(armor: f32) -> f32 {
+    let armor = armor.max(0.);
+    armor / (armor + 300.)
 }
 
 impl Enemy {
+    pub fn new(faction: Faction) -> Self {
+        Self {
+            name: String::new(),
+            faction,
+            weaknesses: HashMap::new(),
+            shield_weaknesses: HashMap::new(),
+            base_level: 1,
+            level: 1,
+            health: 0.,
+            shield: 0.,
+            armor: 0.,
+            overguard: 0.,
+            debuffs: Debuffs::default(),
+        }
+    }
+
+    pub fn at_level(mut self, level: u32) -> Self {
+        self.level = level;
+        self
+    }
+
     pub fn weakness_to(&self, damage_type: &DamageType) -> f32 {
         self.weaknesses.get(damage_type).copied().unwrap_or(1.)
     }
+
+    fn level_difference(&self) -> f32 {
+        self.level.saturating_sub(self.base_level) as f32
+    }
+
+    // Since Update 36 every faction shares the same curves: a polynomial
+    // growth at low levels blended into a slower one at high levels.
+    fn scaling(
+        &self,
+        low: impl Fn(f32) -> f32,
+        high: impl Fn(f32) -> f32,
+        blend: (f32, f32),
+    ) -> f32 {
+        let x = self.level_difference();
+        let t = ((x - blend.0) / (blend.1 - blend.0)).clamp(0., 1.);
+        let s = 3. * t.powi(2) - 2. * t.powi(3);
+        low(x) * (1. - s) + high(x) * s
+    }
+
+    pub fn scaled_health(&self) -> f32 {
+        self.health
+            * self.scaling(
+                |x| 1. + 0.015 * x.powi(2),
+                |x| 1. + 24. * 5f32.sqrt() / 5. * x.sqrt(),
+                (70., 80.),
+            )
+    }
+
+    pub fn scaled_shield(&self) -> f32 {
+        self.shield
+            * self.scaling(
+                |x| 1. + 0.02 * x.powf(1.75),
+                |x| 1. + 1.6 * x.powf(0.75),
+                (70., 80.),
+            )
+    }
+
+    pub fn scaled_armor(&self) -> f32 {
+        self.armor
+            * self.scaling(
+                |x| 1. + 0.005 * x.powf(1.75),
+                |x| 1. + 0.4 * x.powf(0.75),
+                (70., 80.),
+            )
+    }
+
+    pub fn scaled_overguard(&self) -> f32 {
+        self.overguard
+            * self.scaling(
+                |x| 1. + 0.0015 * x.powi(4),
+                |x| 1. + 260. * x.powf(0.9),
+                (45., 50.),
+            )
+    }
+
+    pub fn scaled_pool(&self, pool: Pool) -> f32 {
+        match pool {
+            Pool::Overguard => self.scaled_overguard(),
+            Pool::Shield => self.scaled_shield(),
+            Pool::Health => self.scaled_health(),
+        }
+    }
+
+    /// Fraction of health damage removed by armor.
+    pub fn armor_reduction(&self) -> f32 {
+        armor_reduction((self.scaled_armor() * self.debuffs.armor_multiplier()).min(ARMOR_CAP))
+    }
+
+    /// Whether damage of this type skips the pool and goes straight to health.
+    pub fn bypasses(&self, pool: Pool, damage_type: &DamageType) -> bool {
+        match pool {
+            Pool::Shield => matches!(
+                damage_type,
+                DamageType::Elemental(Element::Primary(PrimaryElement::Toxin))
+            ),
+            Pool::Overguard | Pool::Health => false,
+        }
+    }
+
+    /// Multiplier applied to damage of the given type dealt to a pool,
+    /// including armor for health and status debuffs.
+    pub fn modifier(&self, pool: Pool, damage_type: &DamageType) -> f32 {
+        match pool {
+            Pool::Overguard => 1.,
+            Pool::Shield => {
+                self.shield_weaknesses
+                    .get(damage_type)
+                    .copied()
+                    .unwrap_or(1.)
+                    * self.debuffs.shield_multiplier()
+            }
+            Pool::Health => {
+                let armor = match damage_type {
+                    DamageType::Special(Special::True) => 1.,
+                    _ => 1. - self.armor_reduction(),
+                };
+                self.faction.health_modifier(damage_type)
+                    * self.weakness_to(damage_type)
+                    * self.debuffs.health_multiplier()
+                    * armor
+            }
+        }
+    }
+}
+
+/// Enemy presets, by name.
+pub struct EnemyLibrary {
+    enemies: HashMap<String, Enemy>,
+}
+
+impl EnemyLibrary {
+    pub fn load(path: &Path) -> Self {
+        Self::try_load(path).expect("Should load enemy library")
+    }
+
+    /// Like `load`, but returns an error instead of panicking if the file
+    /// cannot be read or deserialized.
+    pub fn try_load(path: &Path) -> Result<Self, String> {
+        let file_content = std::fs::read_to_string(path)
+            .map_err(|error| format!("Cannot read {}: {error}", path.display()))?;
+        let enemies = ron::from_str::<Vec<Enemy>>(file_content.as_str())
+            .map_err(|error| format!("Cannot deserialize {}: {error}", path.display()))?;
+        Ok(Self {
+            enemies: enemies.into_iter().map(|e| (e.name.clone(), e)).collect(),
+        })
+    }
+
+    pub fn get(&self, enemy_name: &str) -> Option<&Enemy> {
+        self.enemies.get(enemy_name)
+    }
+
+    /// Finds an enemy by name, optionally prefixed by its level as in
+    /// `Lvl 200 Corrupted Heavy Gunner`. Without one, the enemy is at its
+    /// base level.
+    pub fn parse(&self, description: &str) -> Option<Enemy> {
+        let description = description.trim();
+        let (level, name) = match description.split_once(' ') {
+            Some((prefix, rest))
+                if prefix.eq_ignore_ascii_case("Lvl") || prefix.eq_ignore_ascii_case("Level") =>
+            {
+                let (level, name) = rest.trim_start().split_once(' ')?;
+                (Some(level.parse().ok()?), name.trim())
+            }
+            _ => (None, description),
+        };
+
+        let enemy = self.get(name)?.clone();
+        let level = level.unwrap_or(enemy.base_level);
+        Some(enemy.at_level(level))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_float_eq::assert_f32_near;
+
+    #[test]
+    fn load_enemies() {
+        let enemies = EnemyLibrary::load(Path::new("data/enemies.ron"));
+        let crewman = enemies.get("Crewman").expect("Crewman should exist");
+        assert_eq!(crewman.faction, Faction::Corpus);
+
+        let gunner = enemies
+            .parse("Lvl 200 Corrupted Heavy Gunner")
+            .expect("Corrupted Heavy Gunner should exist");
+        assert_eq!(gunner.faction, Faction::Corrupted);
+        assert_eq!(gunner.level, 200);
+
+        let lancer = enemies.parse("Lancer").expect("Lancer should exist");
+        assert_eq!(lancer.level, lancer.base_level);
+        let lancer = enemies
+            .parse("level 30 Lancer")
+            .expect("Lancer should exist");
+        assert_eq!(lancer.level, 30);
+        assert!(enemies.parse("Lvl high Lancer").is_none());
+        assert!(enemies.parse("Lvl 30 Stalker").is_none());
+
+        assert!(EnemyLibrary::try_load(Path::new("data/missing.ron")).is_err());
+        assert!(EnemyLibrary::try_load(Path::new("data/mods.ron")).is_err());
+    }
+
+    fn heavy_gunner() -> Enemy {
+        Enemy {
+            base_level: 8,
+            health: 300.,
+            armor: 500.,
+            ..Enemy::new(Faction::Grineer)
+        }
+    }
+
+    #[test]
+    fn level_scaling() {
+        let base = heavy_gunner();
+        assert_f32_near!(base.scaled_health(), 300.);
+        assert_f32_near!(base.armor_reduction(), 0.625);
+
+        let low = heavy_gunner().at_level(48);
+        assert_f32_near!(low.scaled_health(), 300. * 25.);
+
+        let high = heavy_gunner().at_level(165);
+        assert_f32_near!(
+            high.scaled_health(),
+            300. * (1. + 24. * 5f32.sqrt() / 5. * 157f32.sqrt())
+        );
+        assert_f32_near!(high.armor_reduction(), 0.9);
+        assert_f32_near!(
+            high.modifier(Pool::Health, &DamageType::Physical(Ips::Impact)),
+            0.15,
+            8
+        );
+    }
 }
@@ -1,20 +1,102 @@
 use crate::{
+    buff::{Buff, BuffEffect},
+    critical::{CritTier, Critical},
     damage::*,
-    enemy::Enemy,
-    mods::{Mod, ModEffect, ModStat},
+    enemy::{Enemy, Pool},
+    mods::{Mod, ModEffect},
+    stacking::Stacks,
+    status::{self, Debuffs},
+    weapon::{Attack, Progenitor},
 };
 
 use std::collections::HashMap;
 
+#[derive(Debug, Clone)]
 pub struct Hit {
-    base_damage: HashMap<DamageType, f32>,
+    attack: Attack,
     mods: Vec<Mod>,
     enemy: Enemy,
+    stacks: Stacks,
+    progenitor: Option<Progenitor>,
+    buffs: Vec<Buff>,
 }
 
 impl Hit {
+    pub fn new(attack: &Attack, mods: Vec<Mod>, enemy: Enemy) -> Self {
+        Self {
+            attack: attack.clone(),
+            mods,
+            enemy,
+            stacks: Stacks::default(),
+            progenitor: None,
+            buffs: vec![],
+        }
+    }
+
+    pub fn attack(&self) -> &Attack {
+        &self.attack
+    }
+
+    pub fn enemy(&self) -> &Enemy {
+        &self.enemy
+    }
+
+    pub fn mods(&self) -> &[Mod] {
+        &self.mods
+    }
+
+    /// Sets the stack count of the mods' conditional stats, which otherwise
+    /// don't apply.
+    pub fn with_stacks(mut self, stacks: Stacks) -> Self {
+        self.stacks = stacks;
+        self
+    }
+
+    pub fn with_debuffs(mut self, debuffs: Debuffs) -> Self {
+        self.enemy.debuffs = debuffs;
+        self
+    }
+
+    pub fn with_progenitor(mut self, progenitor: Option<Progenitor>) -> Self {
+        self.progenitor = progenitor;
+        self
+    }
+
+    pub fn with_buffs(mut self, buffs: Vec<Buff>) -> Self {
+        self.buffs = buffs;
+        self
+    }
+
+    fn buff_effects(&self) -> impl Iterator<Item = BuffEffect> {
+        self.buffs.iter().map(Buff::scaled_effect)
+    }
+
+    /// Bonus damage from the weapon's progenitor, based on the attack's
+    /// unmodded total base damage.
+    fn progenitor_damage(&self) -> Option<(DamageType, f32)> {
+        self.progenitor.map(|progenitor| {
+            let attack_base = self.attack.base_damage.values().sum::<f32>();
+            (progenitor.damage_type, progenitor.bonus * attack_base)
+        })
+    }
+
+    /// Unmodded damage of a type, including the progenitor bonus.
+    fn base_damage(&self, damage_type: &DamageType) -> Option<f32> {
+        let base = self.attack.base_damage.get(damage_type).copied();
+        match self.progenitor_damage() {
+            Some((bonus_type, bonus)) if bonus_type == *damage_type => {
+                Some(base.unwrap_or(0.) + bonus)
+            }
+            _ => base,
+        }
+    }
+
     pub fn total_base(&self) -> f32 {
-        self.base_damage.values().sum()
+        self.attack.base_damage.values().sum::<f32>()
+            + self
+                .progenitor_damage()
+                .map(|(_, bonus)| bonus)
+                .unwrap_or(0.)
     }
 
     pub fn scale(&self) -> f32 {
@@ -26,27 +108,55 @@ impl Hit {
         (value / scale).round() * scale
     }
 
+    /// Quantized damage of each type, scaled by the enemy's unit-specific
+    /// weaknesses only.
     pub fn contributions(&self) -> HashMap<DamageType, f32> {
-        let mut physical_calculator = PhysicalCalculator::new(None);
-        let mut elemental_calculator = ElementalCalculator::new(None);
-
-        for r#mod in self.mods.iter() {
-            for ModStat { effect, .. } in r#mod.stats.iter() {
-                match effect {
-                    ModEffect::Physical(ips, value) => physical_calculator.add(*ips, *value),
-                    ModEffect::Elemental(elem, value) => elemental_calculator.add(*elem, *value),
-                    // We don't care about other mods when calculating contributions
-                    _ => (),
-                }
+        self.contributions_with(|damage_type| self.enemy.weakness_to(damage_type))
+    }
+
+    /// Modded contributions, plus damage added by buffs as a share of the
+    /// modded damage.
+    fn contributions_with(
+        &self,
+        modifier: impl Fn(&DamageType) -> f32,
+    ) -> HashMap<DamageType, f32> {
+        let mut result = self.modded_contributions(&modifier);
+
+        let added = self
+            .buff_effects()
+            .filter_map(|effect| match effect {
+                BuffEffect::AddedDamage(damage_type, share) => Some((damage_type, share)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if !added.is_empty() {
+            let modded = self.modded_contributions(|_| 1.).values().sum::<f32>();
+            for (damage_type, share) in added {
+                *result.entry(damage_type).or_insert(0.) += modded * share * modifier(&damage_type);
+            }
+        }
+
+        result
+    }
+
+    fn modded_contributions(
+        &self,
+        modifier: impl Fn(&DamageType) -> f32,
+    ) -> HashMap<DamageType, f32> {
+        let total_base = self.total_base();
+        let mut physical_calculator = PhysicalCalculator::new();
+
+        for effect in self.effects() {
+            if let ModEffect::Physical(ips, value) = effect {
+                physical_calculator.add(ips, value);
             }
         }
 
         let base = [Ips::Impact, Ips::Puncture, Ips::Slash]
             .into_iter()
             .flat_map(|ips| {
-                self.base_damage
-                    .get(&DamageType::Physical(ips))
-                    .map(|v| self.quantize(*v) * self.enemy.weakness_to(&DamageType::Physical(ips)))
+                self.base_damage(&DamageType::Physical(ips))
+                    .map(|v| self.quantize(v) * modifier(&DamageType::Physical(ips)))
                     .map(|v| (DamageType::Physical(ips), v))
             });
 
@@ -55,93 +165,415 @@ impl Hit {
             .into_iter()
             .map(|(ips, value)| {
                 let value = self
-                    .base_damage
-                    .get(&DamageType::Physical(ips))
-                    .map(|base| {
-                        self.quantize(value * base)
-                            * self.enemy.weakness_to(&DamageType::Physical(ips))
-                    })
+                    .base_damage(&DamageType::Physical(ips))
+                    .map(|base| self.quantize(value * base) * modifier(&DamageType::Physical(ips)))
                     .unwrap_or(0.);
                 (DamageType::Physical(ips), value)
             });
 
-        let total_base = self.total_base();
-        let elemental = elemental_calculator
-            .finalize()
-            .into_iter()
-            .map(|(elem, value)| {
-                (
-                    DamageType::Elemental(elem),
-                    self.quantize(value * total_base)
-                        * self.enemy.weakness_to(&DamageType::Elemental(elem)),
-                )
-            });
+        let elemental = self.combination_trace().into_iter().map(|combination| {
+            let damage_type = DamageType::Elemental(combination.element);
+            (
+                damage_type,
+                self.quantize(combination.value * total_base) * modifier(&damage_type),
+            )
+        });
 
         let mut result = HashMap::new();
+        let base_multiplier = self.base_multiplier();
 
         base.chain(physical)
             .chain(elemental)
-            .for_each(|(dt, value)| *result.entry(dt).or_insert(0.) += value);
+            .for_each(|(dt, value)| *result.entry(dt).or_insert(0.) += value * base_multiplier);
 
         result
     }
 
-    pub fn total_quantized(&self) -> f32 {
-        let mut bane = 1.;
+    /// Elements of this hit after combination. Modded elements combine in mod
+    /// order, then the attack's innate elements, then the progenitor bonus.
+    pub fn combination_trace(&self) -> Vec<Combination> {
+        let total_base = self.total_base();
+        let mut calculator = ElementalCalculator::default();
 
-        for r#mod in self.mods.iter() {
-            for ModStat { effect, .. } in r#mod.stats.iter() {
-                match effect {
-                    ModEffect::Bane(faction, value) if self.enemy.faction == *faction => {
-                        bane += value
-                    }
-                    _ => (),
-                }
+        for (name, effect) in self.named_effects() {
+            if let ModEffect::Elemental(element, value) = effect {
+                calculator.add(element, value, name);
             }
         }
-        self.contributions().values().sum::<f32>() * bane
+
+        for element in Element::ALL {
+            if let Some(base) = self.attack.base_damage.get(&DamageType::Elemental(element)) {
+                calculator.add(element, base / total_base, INNATE);
+            }
+        }
+
+        if let Some((DamageType::Elemental(element), bonus)) = self.progenitor_damage() {
+            calculator.add(element, bonus / total_base, PROGENITOR);
+        }
+
+        calculator.finalize()
     }
 
-    pub fn status_chance(&self) -> HashMap<DamageType, f32> {
-        let contributions = self.contributions();
-        let total_dmg = contributions.values().sum::<f32>();
+    /// Multiplier from base damage mods such as Serration, and buffs that add
+    /// to them.
+    fn base_multiplier(&self) -> f32 {
+        1. + self.sum_effects(|effect| match effect {
+            ModEffect::Damage(value) => Some(value),
+            _ => None,
+        }) + self
+            .buff_effects()
+            .filter_map(|effect| match effect {
+                BuffEffect::BaseDamage(value) => Some(value),
+                _ => None,
+            })
+            .sum::<f32>()
+    }
+
+    /// Multiplier from faction mods and buffs that multiply damage like
+    /// them.
+    fn bane(&self) -> f32 {
+        self.faction_multiplier() * self.buff_multiplier()
+    }
+
+    fn faction_multiplier(&self) -> f32 {
+        1. + self
+            .effects()
+            .filter_map(|effect| match effect {
+                ModEffect::Bane(faction, value) if self.enemy.faction == faction => Some(value),
+                _ => None,
+            })
+            .sum::<f32>()
+    }
 
+    fn buff_multiplier(&self) -> f32 {
+        1. + self
+            .buff_effects()
+            .filter_map(|effect| match effect {
+                BuffEffect::DamageMultiplier(value) => Some(value),
+                _ => None,
+            })
+            .sum::<f32>()
+    }
+
+    pub fn total_quantized(&self) -> f32 {
+        self.contributions().values().sum::<f32>() * self.bane()
+    }
+
+    /// Damage this hit deals to one of the enemy's pools, after faction,
+    /// armor and pool modifiers. Damage types that bypass the pool are left out.
+    pub fn damage_to(&self, pool: Pool) -> f32 {
+        let contributions = self.contributions_with(|damage_type| {
+            if self.enemy.bypasses(pool, damage_type) {
+                0.
+            } else {
+                self.enemy.modifier(pool, damage_type)
+            }
+        });
+        contributions.values().sum::<f32>() * self.bane()
+    }
+
+    /// Damage from types that bypass the given pool, dealt to health instead.
+    pub fn damage_bypassing(&self, pool: Pool) -> f32 {
+        let contributions = self.contributions_with(|damage_type| {
+            if self.enemy.bypasses(pool, damage_type) {
+                self.enemy.modifier(Pool::Health, damage_type)
+            } else {
+                0.
+            }
+        });
+        contributions.values().sum::<f32>() * self.bane()
+    }
+
+    /// Final status chance of a single pellet, with mods applied additively to
+    /// the weapon's base chance.
+    pub fn total_status_chance(&self) -> f32 {
         let mod_sc = self
-            .mods
-            .iter()
-            .filter_map(|m| {
-                m.stats.iter().find_map(|ModStat { effect, .. }| {
-                    if let ModEffect::StatusChance(sc) = effect {
-                        Some(sc)
-                    } else {
-                        None
-                    }
-                })
+            .effects()
+            .filter_map(|effect| match effect {
+                ModEffect::StatusChance(sc) => Some(sc),
+                _ => None,
             })
             .sum::<f32>();
-        // TODO: Create Weapon data structure
-        let total_sc = mod_sc;
-        todo!();
+
+        self.attack.status_chance * (1. + mod_sc)
+    }
+
+    /// Chance of each damage type being proc'd, weighted by its share of the
+    /// quantized modded damage. Enemy weaknesses and damage added by buffs
+    /// don't change the weights.
+    pub fn status_chance(&self) -> HashMap<DamageType, f32> {
+        let contributions = self.modded_contributions(|_| 1.);
+        let total_dmg = contributions.values().sum::<f32>();
+        let total_sc = self.total_status_chance();
+
+        if total_dmg <= 0. {
+            return HashMap::new();
+        }
 
         contributions
             .into_iter()
             .map(|(ty, damage)| (ty, (damage / total_dmg) * total_sc))
             .collect()
     }
+
+    /// Expected number of status procs per shot. Status chance above 100%
+    /// guarantees a proc per pellet, with the remainder as the chance of an
+    /// additional one.
+    pub fn procs_per_shot(&self) -> f32 {
+        self.total_status_chance() * self.multishot()
+    }
+
+    pub fn critical(&self) -> Critical {
+        let (mod_cc, mod_cd) = self
+            .effects()
+            .fold((0., 0.), |(cc, cd), effect| match effect {
+                ModEffect::CriticalChance(value) => (cc + value, cd),
+                ModEffect::CriticalDamage(value) => (cc, cd + value),
+                _ => (cc, cd),
+            });
+
+        Critical {
+            chance: self.attack.critical_chance * (1. + mod_cc),
+            multiplier: self.attack.critical_multiplier * (1. + mod_cd)
+                + self.enemy.debuffs.critical_damage_bonus(),
+        }
+    }
+
+    /// Damage dealt by each critical tier this hit can land, with its chance.
+    pub fn damage_per_tier(&self) -> Vec<CritTier> {
+        self.critical().tiers(self.total_quantized())
+    }
+
+    /// Average damage of this hit once critical hits are accounted for.
+    pub fn expected_damage(&self) -> f32 {
+        self.total_quantized() * self.critical().average_multiplier()
+    }
+
+    /// Average pellets fired per shot.
+    pub fn multishot(&self) -> f32 {
+        let mod_multishot = self.sum_effects(|effect| match effect {
+            ModEffect::Multishot(value) => Some(value),
+            _ => None,
+        });
+        self.attack.multishot * (1. + mod_multishot)
+    }
+
+    /// Pellets every shot fires, and the chance of firing one more.
+    pub fn pellets(&self) -> (u32, f32) {
+        let multishot = self.multishot().max(0.);
+        (multishot.floor() as u32, multishot.fract())
+    }
+
+    pub fn fire_rate(&self) -> f32 {
+        let mod_fire_rate = self.sum_effects(|effect| match effect {
+            ModEffect::FireRate(value) => Some(value),
+            _ => None,
+        });
+        self.attack.fire_rate * (1. + mod_fire_rate)
+    }
+
+    /// Charge time, shortened by fire rate mods.
+    pub fn charge_time(&self) -> f32 {
+        self.attack.charge_time * self.attack.fire_rate / self.fire_rate()
+    }
+
+    /// Seconds between two shots. Charged attacks can't fire faster than
+    /// they charge.
+    pub fn shot_interval(&self) -> f32 {
+        (1. / self.fire_rate()).max(self.charge_time())
+    }
+
+    pub fn magazine(&self) -> u32 {
+        let mod_magazine = self.sum_effects(|effect| match effect {
+            ModEffect::MagazineCapacity(value) => Some(value),
+            _ => None,
+        });
+        match self.attack.magazine {
+            0 => 0,
+            magazine => ((magazine as f32 * (1. + mod_magazine)).round() as u32).max(1),
+        }
+    }
+
+    /// Reload time in seconds.
+    pub fn reload(&self) -> f32 {
+        let mod_reload_speed = self.sum_effects(|effect| match effect {
+            ModEffect::ReloadSpeed(value) => Some(value),
+            _ => None,
+        });
+        self.attack.reload / (1. + mod_reload_speed)
+    }
+
+    /// Average damage per second to health while the magazine lasts,
+    /// including damage over time at that fire rate.
+    pub fn burst_dps(&self) -> f32 {
+        self.dps_at(1. / self.shot_interval())
+    }
+
+    /// Shots per second averaged over whole magazines and their reloads.
+    fn sustained_shots_per_second(&self) -> f32 {
+        let magazine = self.magazine();
+        if magazine == 0 {
+            return 1. / self.shot_interval();
+        }
+        let firing = magazine as f32 * self.shot_interval();
+        magazine as f32 / (firing + self.reload())
+    }
+
+    /// Average damage per second to health including reloads and damage over
+    /// time.
+    pub fn sustained_dps(&self) -> f32 {
+        self.dps_at(self.sustained_shots_per_second())
+    }
+
+    /// Damage per second to health of direct hits and damage over time when
+    /// firing `shots_per_second` shots, both against the steady-state debuffs
+    /// they cause.
+    fn dps_at(&self, shots_per_second: f32) -> f32 {
+        let (debuffs, _) = self.status_steady_state(shots_per_second);
+        let debuffed = self.clone().with_debuffs(debuffs);
+        let (_, dot_dps) = debuffed.status_steady_state(shots_per_second);
+        let direct = debuffed.damage_to(Pool::Health) * debuffed.critical().average_multiplier();
+        direct * self.multishot() * shots_per_second + dot_dps
+    }
+
+    fn sum_effects(&self, select: impl Fn(ModEffect) -> Option<f32>) -> f32 {
+        self.effects().filter_map(select).sum()
+    }
+
+    pub fn status_duration(&self, damage_type: &DamageType) -> f32 {
+        let mod_duration = self.sum_effects(|effect| match effect {
+            ModEffect::StatusDuration(value) => Some(value),
+            _ => None,
+        });
+        status::base_duration(damage_type) * (1. + mod_duration)
+    }
+
+    /// Damage of each tick of a damage over time proc of the given type, before
+    /// critical hits and enemy modifiers. Faction mods apply twice to damage
+    /// over time, and elemental procs are also multiplied by the mods of their
+    /// element.
+    pub fn dot_tick_damage(&self, damage_type: &DamageType) -> Option<f32> {
+        let mod_status_damage = self.sum_effects(|effect| match effect {
+            ModEffect::StatusDamage(value) => Some(value),
+            _ => None,
+        });
+        let elemental = match damage_type {
+            DamageType::Elemental(element) => 1. + self.elemental_mods(*element),
+            _ => 1.,
+        };
+
+        status::dot_multiplier(damage_type).map(|multiplier| {
+            multiplier
+                * self.total_base()
+                * self.base_multiplier()
+                * self.faction_multiplier().powi(2)
+                * self.buff_multiplier()
+                * elemental
+                * (1. + mod_status_damage)
+        })
+    }
+
+    /// Total value of the elemental mods that combined into an element.
+    fn elemental_mods(&self, element: Element) -> f32 {
+        let Some(combination) = self
+            .combination_trace()
+            .into_iter()
+            .find(|combination| combination.element == element)
+        else {
+            return 0.;
+        };
+        self.named_effects()
+            .filter_map(|(name, effect)| match effect {
+                ModEffect::Elemental(_, value)
+                    if combination.sources.iter().any(|source| source == name) =>
+                {
+                    Some(value)
+                }
+                _ => None,
+            })
+            .sum()
+    }
+
+    /// Total damage a single proc of the given type deals to health over its
+    /// duration. Procs inherit the hit's average critical multiplier.
+    pub fn dot_damage(&self, damage_type: &DamageType) -> f32 {
+        let Some(tick) = self.dot_tick_damage(damage_type) else {
+            return 0.;
+        };
+        let ticks = (self.status_duration(damage_type) / status::TICK_INTERVAL).floor() + 1.;
+        let dot_type = status::dot_damage_type(damage_type);
+
+        tick * ticks
+            * self.critical().average_multiplier()
+            * self.enemy.modifier(Pool::Health, &dot_type)
+    }
+
+    /// Steady-state debuffs on the enemy and damage over time per second when
+    /// firing `shots_per_second` shots indefinitely.
+    pub fn status_steady_state(&self, shots_per_second: f32) -> (Debuffs, f32) {
+        let mut debuffs = Debuffs::default();
+        let mut dot_dps = 0.;
+
+        for (damage_type, chance) in self.status_chance() {
+            let procs_per_second = chance * self.multishot() * shots_per_second;
+            debuffs.set(
+                damage_type,
+                procs_per_second * self.status_duration(&damage_type),
+            );
+            dot_dps += procs_per_second * self.dot_damage(&damage_type);
+        }
+
+        (debuffs, dot_dps)
+    }
+
+    /// Damage over time per second once the enemy is under steady-state
+    /// debuffs from sustained fire.
+    pub fn status_dps(&self) -> f32 {
+        self.status_dps_at(self.sustained_shots_per_second())
+    }
+
+    fn status_dps_at(&self, shots_per_second: f32) -> f32 {
+        let (debuffs, _) = self.status_steady_state(shots_per_second);
+        let (_, dot_dps) = self
+            .clone()
+            .with_debuffs(debuffs)
+            .status_steady_state(shots_per_second);
+        dot_dps
+    }
+
+    /// Effects of every mod at its rank, with conditional stats scaled by their
+    /// current stack count.
+    fn effects(&self) -> impl Iterator<Item = ModEffect> {
+        self.named_effects().map(|(_, effect)| effect)
+    }
+
+    /// Same as `effects`, along with the name of the mod they come from.
+    fn named_effects(&self) -> impl Iterator<Item = (&str, ModEffect)> {
+        self.mods.iter().flat_map(|r#mod| {
+            r#mod
+                .ranked_stats()
+                .into_iter()
+                .enumerate()
+                .filter_map(|(index, stat)| {
+                    if !stat.is_conditional() {
+                        return Some((r#mod.name.as_str(), stat.effect));
+                    }
+                    let stacks = self.stacks.get(&r#mod.name, index);
+                    (stacks > 0.).then(|| (r#mod.name.as_str(), stat.effect.scaled(stacks)))
+                })
+        })
+    }
 }
 
+/// Physical progenitor bonuses are part of the base damage these multipliers
+/// apply to, so they are not tracked here.
 struct PhysicalCalculator {
     ips: Vec<(Ips, f32)>,
-    // TODO: Add lich damage calculation (based on total or base?)
-    _lich: Option<(Ips, f32)>,
 }
 
 impl PhysicalCalculator {
-    pub fn new(lich: Option<(Ips, f32)>) -> Self {
-        Self {
-            ips: vec![],
-            _lich: lich,
-        }
+    pub fn new() -> Self {
+        Self { ips: vec![] }
     }
 
     pub fn add(&mut self, ips: Ips, value: f32) {
@@ -159,98 +591,124 @@ impl PhysicalCalculator {
     }
 }
 
-struct ElementalCalculator {
-    primary: Vec<(PrimaryElement, f32)>,
-    secondary: Vec<(SecondaryElement, f32)>,
-    _lich: Option<(Element, f32)>,
+/// An element dealt by a hit once elements are combined, as a share of total
+/// base damage, with the sources that merged into it in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Combination {
+    pub element: Element,
+    pub value: f32,
+    pub sources: Vec<String>,
 }
 
-macro_rules! map {
-    {$($key:expr => $value:expr),* $(,)?} => {
-        HashMap::from([$(($key, $value)),*])
-    };
-}
+/// Source name of the attack's innate elements in a `Combination`.
+pub const INNATE: &str = "Innate";
+/// Source name of the progenitor bonus in a `Combination`.
+pub const PROGENITOR: &str = "Progenitor";
 
-impl ElementalCalculator {
-    // TODO: LazyStatic
-    fn combinations() -> HashMap<SecondaryElement, (PrimaryElement, PrimaryElement)> {
-        use PrimaryElement::*;
-        use SecondaryElement::*;
-
-        map! {
-            Blast => (Cold, Heat),
-            Viral => (Cold, Toxin),
-            Magnetic => (Cold, Electricity),
-            Gas => (Heat, Toxin),
-            Radiation => (Heat, Electricity),
-            Corrosive => (Toxin, Electricity),
-        }
-    }
+const COMBINATIONS: [(SecondaryElement, PrimaryElement, PrimaryElement); 6] = {
+    use PrimaryElement::*;
+    use SecondaryElement::*;
 
-    fn new(lich: Option<(Element, f32)>) -> Self {
-        Self {
-            primary: vec![],
-            secondary: vec![],
-            _lich: lich,
-        }
-    }
+    [
+        (Blast, Cold, Heat),
+        (Viral, Cold, Toxin),
+        (Magnetic, Cold, Electricity),
+        (Gas, Heat, Toxin),
+        (Radiation, Heat, Electricity),
+        (Corrosive, Toxin, Electricity),
+    ]
+};
 
-    fn add(&mut self, ty: Element, value: f32) {
-        match ty {
-            Element::Primary(e) => self.primary.push((e, value)),
-            Element::Secondary(e) => self.secondary.push((e, value)),
-        }
-    }
+fn combine(l: PrimaryElement, r: PrimaryElement) -> Option<SecondaryElement> {
+    COMBINATIONS
+        .iter()
+        .find(|(_, a, b)| (*a, *b) == (l, r) || (*a, *b) == (r, l))
+        .map(|(secondary, _, _)| *secondary)
+}
 
-    fn finalize(self) -> HashMap<Element, f32> {
-        let mut result: HashMap<Element, f32> = Default::default();
+/// Combines elements in the order they are added. Each primary element joins
+/// its earlier occurrence, or the secondary element it already formed, or
+/// combines with the first unpaired primary element it can.
+#[derive(Default)]
+struct ElementalCalculator {
+    /// Combinations in order, and the primary elements each one was formed from.
+    entries: Vec<(Combination, Option<(PrimaryElement, PrimaryElement)>)>,
+}
 
-        for (elem, value) in self.primary {
-            Self::add_primary(&mut result, elem, value);
-        }
+impl ElementalCalculator {
+    fn add(&mut self, element: Element, value: f32, source: &str) {
+        let index = match element {
+            Element::Primary(primary) => self.primary_entry(primary),
+            Element::Secondary(_) => self.entries.iter().position(|(c, _)| c.element == element),
+        };
 
-        for (elem, value) in self.secondary {
-            Self::add_secondary(&mut result, elem, value);
-        }
+        let index = index.unwrap_or_else(|| {
+            self.entries.push((
+                Combination {
+                    element,
+                    value: 0.,
+                    sources: vec![],
+                },
+                None,
+            ));
+            self.entries.len() - 1
+        });
 
-        result
+        let (combination, _) = &mut self.entries[index];
+        combination.value += value;
+        if !combination.sources.iter().any(|s| s == source) {
+            combination.sources.push(source.to_owned());
+        }
     }
 
-    fn add_primary(result: &mut HashMap<Element, f32>, ty: PrimaryElement, value: f32) {
-        let map = Self::combinations();
+    /// Index of the entry a primary element is added to, combining it with an
+    /// unpaired primary element if needed.
+    fn primary_entry(&mut self, primary: PrimaryElement) -> Option<usize> {
+        if let Some(index) = self.entries.iter().position(|(c, formed_from)| {
+            c.element == Element::Primary(primary)
+                || formed_from.is_some_and(|(l, r)| l == primary || r == primary)
+        }) {
+            return Some(index);
+        }
 
-        let mut was_combined = false;
-        for (secondary, (l, r)) in map.iter() {
-            if *l != ty && *r != ty {
-                continue;
-            }
-            let other_elem = Element::Primary(*if *l == ty { r } else { l });
+        let (index, secondary, other) =
+            self.entries
+                .iter()
+                .enumerate()
+                .find_map(|(index, (c, _))| match c.element {
+                    Element::Primary(other) => {
+                        combine(other, primary).map(|secondary| (index, secondary, other))
+                    }
+                    Element::Secondary(_) => None,
+                })?;
 
-            // Secondary element is already registered.
-            if let Some(cur) = result.get_mut(&Element::Secondary(*secondary)) {
-                *cur += value;
-                was_combined = true;
-            }
-            // Combine with the other required primary element to produce the
-            // secondary element.
-            else if let Some(other_value) = result.get(&other_elem)
-                && *other_value > 0.
-            {
-                result.insert(Element::Secondary(*secondary), value + *other_value);
-                result.insert(other_elem, 0.);
-                was_combined = true;
-            }
-        }
+        self.entries[index].0.element = Element::Secondary(secondary);
+        self.entries[index].1 = Some((other, primary));
 
-        if was_combined {
-            return;
+        // Merge a secondary element added directly, such as from a mod, into
+        // the newly formed one.
+        if let Some(existing) = self
+            .entries
+            .iter()
+            .enumerate()
+            .position(|(i, (c, _))| i != index && c.element == Element::Secondary(secondary))
+        {
+            let (merged, _) = self.entries.remove(existing);
+            let index = if existing < index { index - 1 } else { index };
+            let (combination, _) = &mut self.entries[index];
+            combination.value += merged.value;
+            combination.sources.extend(merged.sources);
+            return Some(index);
         }
 
-        *result.entry(Element::Primary(ty)).or_insert(0.) += value;
+        Some(index)
     }
 
-    fn add_secondary(result: &mut HashMap<Element, f32>, ty: SecondaryElement, value: f32) {
-        *result.entry(Element::Secondary(ty)).or_insert(0.) += value;
+    fn finalize(self) -> Vec<Combination> {
+        self.entries
+            .into_iter()
+            .map(|(combination, _)| combination)
+            .collect()
     }
 }
 
@@ -267,19 +725,40 @@ mod test {
         ModLibrary::load(Path::new("data/mods.ron"))
     }
 
+    fn attack(base_damage: HashMap<DamageType, f32>) -> Attack {
+        Attack {
+            name: "Normal Attack".to_owned(),
+            base_damage,
+            critical_chance: 0.,
+            critical_multiplier: 1.,
+            status_chance: 0.,
+            fire_rate: 1.,
+            charge_time: 0.,
+            multishot: 1.,
+            magazine: 1,
+            reload: 0.,
+            ammo: Some(1),
+        }
+    }
+
     #[test]
     fn basic_ips() {
         let hit = Hit {
-            base_damage: [
-                (DamageType::Physical(Ips::Impact), 30.),
-                (DamageType::Physical(Ips::Puncture), 30.),
-                (DamageType::Physical(Ips::Slash), 40.),
-            ]
-            .into(),
+            attack: attack(
+                [
+                    (DamageType::Physical(Ips::Impact), 30.),
+                    (DamageType::Physical(Ips::Puncture), 30.),
+                    (DamageType::Physical(Ips::Slash), 40.),
+                ]
+                .into(),
+            ),
             mods: vec![],
+            stacks: Stacks::default(),
+            progenitor: None,
+            buffs: vec![],
             enemy: Enemy {
-                faction: Faction::Infested,
                 weaknesses: [(DamageType::Physical(Ips::Slash), 1.5)].into(),
+                ..Enemy::new(Faction::Infested)
             },
         };
 
@@ -304,37 +783,51 @@ mod test {
         let hellfire = Mod {
             name: "Hellfire".to_owned(),
             stats: vec![ModEffect::Elemental(Element::Primary(PrimaryElement::Heat), 0.9).into()],
+            ..Default::default()
         };
 
         let piercing_caliber = Mod {
             name: "Piercing Caliber".to_owned(),
             stats: vec![ModEffect::Physical(Ips::Puncture, 1.2).into()],
+            ..Default::default()
         };
 
         let valence_formation_gas = Mod {
             name: "Valence Formation - Gas".to_owned(),
             stats: vec![ModEffect::Elemental(Element::Secondary(SecondaryElement::Gas), 2.).into()],
+            ..Default::default()
         };
 
-        let hit = Hit {
+        let nagantaka_prime = Attack {
+            name: "Normal Attack".to_owned(),
             base_damage: [
                 (DamageType::Physical(Ips::Impact), 1.7),
                 (DamageType::Physical(Ips::Puncture), 15.6),
                 (DamageType::Physical(Ips::Slash), 155.7),
             ]
             .into(),
-            mods: vec![
+            critical_chance: 0.25,
+            critical_multiplier: 2.3,
+            status_chance: 0.39,
+            fire_rate: 2.33,
+            charge_time: 0.,
+            multishot: 1.,
+            magazine: 7,
+            reload: 0.4,
+            ammo: Some(72),
+        };
+
+        let hit = Hit::new(
+            &nagantaka_prime,
+            vec![
                 cryo_rounds,
                 malignant_force,
                 hellfire,
                 piercing_caliber,
                 valence_formation_gas,
             ],
-            enemy: Enemy {
-                faction: Faction::Infested,
-                weaknesses: HashMap::new(),
-            },
-        };
+            Enemy::new(Faction::Infested),
+        );
 
         assert_f32_near!(hit.total_base(), 173.);
         assert_f32_near!(hit.scale(), 10.8125);
@@ -385,4 +878,391 @@ mod test {
 
         assert_f32_near!(hit.total_quantized(), 940.6875);
     }
+
+    #[test]
+    fn status_chance() {
+        let mods = get_mods();
+
+        let mut high_status = attack(
+            [
+                (DamageType::Physical(Ips::Impact), 40.),
+                (DamageType::Physical(Ips::Slash), 60.),
+            ]
+            .into(),
+        );
+        high_status.status_chance = 0.8;
+        high_status.multishot = 2.;
+
+        let hit = Hit::new(
+            &high_status,
+            vec![
+                mods.get("Malignant Force")
+                    .expect("Malignant Force should exist")
+                    .clone(),
+            ],
+            Enemy::new(Faction::Grineer),
+        );
+
+        assert_f32_near!(hit.total_status_chance(), 1.28);
+        assert_f32_near!(hit.procs_per_shot(), 2.56);
+
+        // 60% Toxin on a 100 damage weapon quantizes to 62.5 out of 162.5.
+        let status_chance = hit.status_chance();
+        assert_f32_near!(hit.total_quantized(), 162.5);
+        assert_f32_near!(status_chance.values().sum::<f32>(), 1.28);
+        assert_f32_near!(
+            *status_chance
+                .get(&DamageType::Elemental(Element::Primary(
+                    PrimaryElement::Toxin
+                )))
+                .unwrap(),
+            62.5 / 162.5 * 1.28
+        );
+
+        // Unit weaknesses change the damage dealt, not the proc weights.
+        let weak_to_toxin = Hit::new(
+            &high_status,
+            hit.mods().to_vec(),
+            Enemy {
+                weaknesses: [(
+                    DamageType::Elemental(Element::Primary(PrimaryElement::Toxin)),
+                    2.,
+                )]
+                .into(),
+                ..Enemy::new(Faction::Grineer)
+            },
+        );
+        assert_eq!(weak_to_toxin.status_chance(), status_chance);
+    }
+
+    #[test]
+    fn critical() {
+        let mut crit_attack = attack([(DamageType::Physical(Ips::Puncture), 100.)].into());
+        crit_attack.critical_chance = 0.5;
+        crit_attack.critical_multiplier = 2.;
+
+        let point_strike = Mod {
+            name: "Point Strike".to_owned(),
+            stats: vec![ModEffect::CriticalChance(1.5).into()],
+            ..Default::default()
+        };
+
+        let vital_sense = Mod {
+            name: "Vital Sense".to_owned(),
+            stats: vec![ModEffect::CriticalDamage(1.2).into()],
+            ..Default::default()
+        };
+
+        let hit = Hit::new(
+            &crit_attack,
+            vec![point_strike, vital_sense],
+            Enemy::new(Faction::Corpus),
+        );
+
+        let critical = hit.critical();
+        assert_f32_near!(critical.chance, 1.25);
+        assert_f32_near!(critical.multiplier, 4.4);
+
+        let tiers = hit.damage_per_tier();
+        assert_eq!(tiers[0].tier, 1);
+        assert_f32_near!(tiers[0].damage, 440.);
+        assert_eq!(tiers[1].tier, 2);
+        assert_f32_near!(tiers[1].damage, 780.);
+
+        assert_f32_near!(hit.expected_damage(), 100. * (1. + 1.25 * 3.4));
+    }
+
+    #[test]
+    fn damage_to_pools() {
+        let infected_clip = Mod {
+            name: "Infected Clip".to_owned(),
+            stats: vec![ModEffect::Elemental(Element::Primary(PrimaryElement::Toxin), 0.5).into()],
+            ..Default::default()
+        };
+
+        let hit = Hit::new(
+            &attack([(DamageType::Physical(Ips::Impact), 100.)].into()),
+            vec![infected_clip],
+            Enemy {
+                base_level: 1,
+                health: 100.,
+                shield: 100.,
+                armor: 300.,
+                ..Enemy::new(Faction::Grineer)
+            },
+        );
+
+        // Impact is Grineer's weakness, and armor halves all health damage.
+        assert_f32_near!(hit.damage_to(Pool::Health), (150. + 50.) * 0.5);
+        // Toxin skips shields entirely.
+        assert_f32_near!(hit.damage_to(Pool::Shield), 100.);
+        assert_f32_near!(hit.damage_bypassing(Pool::Shield), 25.);
+    }
+
+    #[test]
+    fn damage_over_time() {
+        let mut slash = attack([(DamageType::Physical(Ips::Slash), 100.)].into());
+        slash.status_chance = 0.5;
+
+        let hit = Hit::new(
+            &slash,
+            vec![Mod {
+                name: "Status Build".to_owned(),
+                stats: vec![
+                    ModEffect::StatusDuration(1.).into(),
+                    ModEffect::StatusDamage(0.2).into(),
+                ],
+                ..Default::default()
+            }],
+            Enemy::new(Faction::Grineer),
+        );
+        let bleed = DamageType::Physical(Ips::Slash);
+
+        assert_f32_near!(hit.status_duration(&bleed), 12.);
+        assert_f32_near!(hit.dot_tick_damage(&bleed).unwrap(), 42.);
+        assert_f32_near!(hit.dot_damage(&bleed), 42. * 13.);
+        // Half a proc per second.
+        assert_f32_near!(hit.status_dps(), 42. * 13. * 0.5);
+        assert!(hit.burst_dps() >= hit.sustained_dps());
+
+        // Faction mods apply twice to ticks, elemental mods once more to
+        // elemental ones.
+        let toxin = DamageType::Elemental(Element::Primary(PrimaryElement::Toxin));
+        let hit = Hit::new(
+            &slash,
+            vec![
+                Mod {
+                    name: "Bane of Grineer".to_owned(),
+                    stats: vec![ModEffect::Bane(Faction::Grineer, 0.3).into()],
+                    ..Default::default()
+                },
+                Mod {
+                    name: "Infected Clip".to_owned(),
+                    stats: vec![
+                        ModEffect::Elemental(Element::Primary(PrimaryElement::Toxin), 0.9).into(),
+                    ],
+                    ..Default::default()
+                },
+            ],
+            Enemy::new(Faction::Grineer),
+        );
+        assert_f32_near!(hit.dot_tick_damage(&bleed).unwrap(), 35. * 1.69);
+        assert_f32_near!(hit.dot_tick_damage(&toxin).unwrap(), 50. * 1.69 * 1.9);
+    }
+
+    #[test]
+    fn conditional_stacks() {
+        use crate::{
+            mods::{ModStat, Stacking, StackingBehaviour, TimeoutBehaviour, Trigger},
+            stacking::StackEngine,
+        };
+        use std::time::Duration;
+
+        let mut status = attack([(DamageType::Physical(Ips::Impact), 100.)].into());
+        status.status_chance = 0.1;
+
+        let galvanized_shot = Mod {
+            name: "Galvanized Shot".to_owned(),
+            stats: vec![
+                ModEffect::StatusChance(0.8).into(),
+                ModStat {
+                    trigger: Some(Trigger::Kill),
+                    stacking: Some(Stacking {
+                        max: 3,
+                        behaviour: StackingBehaviour::Timed {
+                            duration: Duration::from_secs(14),
+                            timeout: TimeoutBehaviour::Drop,
+                            resets_on_stack: true,
+                        },
+                    }),
+                    effect: ModEffect::StatusChance(0.4),
+                },
+            ],
+            ..Default::default()
+        };
+        let mods = vec![galvanized_shot];
+        let hit = Hit::new(&status, mods.clone(), Enemy::new(Faction::Corpus));
+
+        assert_f32_near!(hit.total_status_chance(), 0.18);
+
+        let hit = hit.with_stacks(Stacks::max(&mods));
+        assert_f32_near!(hit.total_status_chance(), 0.3);
+
+        // A kill every 5s keeps 1, 2 then 3 stacks up for 5s each.
+        let average = StackEngine::average(&mods, &[(Trigger::Kill, 0.2)], 15.);
+        let hit = hit.with_stacks(average);
+        assert_f32_near!(hit.total_status_chance(), 0.26);
+    }
+
+    #[test]
+    fn base_damage_mods() {
+        let serration = Mod {
+            name: "Serration".to_owned(),
+            stats: vec![ModEffect::Damage(1.65).into()],
+            ..Default::default()
+        };
+
+        let hit = Hit::new(
+            &attack([(DamageType::Physical(Ips::Impact), 100.)].into()),
+            vec![serration],
+            Enemy::new(Faction::Infested),
+        );
+
+        assert_f32_near!(hit.total_quantized(), 265.);
+    }
+
+    #[test]
+    fn debuffed_dps() {
+        let viral = DamageType::Elemental(Element::Secondary(SecondaryElement::Viral));
+        let mut rifle = attack([(viral, 100.)].into());
+        let hit = Hit::new(&rifle, vec![], Enemy::new(Faction::Grineer));
+        assert_f32_near!(hit.burst_dps(), 100.);
+
+        // A Viral proc every second keeps 6 stacks up, which raise direct
+        // damage to health by 100% plus 25% per additional stack.
+        rifle.status_chance = 1.;
+        let hit = Hit::new(&rifle, vec![], Enemy::new(Faction::Grineer));
+        assert_f32_near!(hit.burst_dps(), 100. * 3.25);
+        assert_f32_near!(hit.sustained_dps(), 100. * 3.25);
+    }
+
+    #[test]
+    fn dps() {
+        let mut rifle = attack([(DamageType::Physical(Ips::Impact), 100.)].into());
+        rifle.fire_rate = 5.;
+        rifle.magazine = 10;
+        rifle.reload = 2.;
+
+        let mods = vec![Mod {
+            name: "Rate of Fire".to_owned(),
+            stats: vec![
+                ModEffect::Multishot(0.5).into(),
+                ModEffect::FireRate(1.).into(),
+                ModEffect::MagazineCapacity(0.5).into(),
+                ModEffect::ReloadSpeed(1.).into(),
+            ],
+            ..Default::default()
+        }];
+        let hit = Hit::new(&rifle, mods.clone(), Enemy::new(Faction::Grineer));
+
+        assert_eq!(hit.pellets(), (1, 0.5));
+        assert_eq!(hit.magazine(), 15);
+        assert_f32_near!(hit.reload(), 1.);
+        // Impact is Grineer's weakness.
+        assert_f32_near!(hit.burst_dps(), 100. * 1.5 * 10. * 1.5);
+        // 15 shots over 1.5s, then a 1s reload.
+        assert_f32_near!(hit.sustained_dps(), 100. * 1.5 * 6. * 1.5);
+
+        // Fire rate shortens the charge, which still limits how fast it fires.
+        let mut bow = rifle.clone();
+        bow.fire_rate = 4.;
+        bow.charge_time = 0.5;
+        let hit = Hit::new(&bow, mods, Enemy::new(Faction::Grineer));
+        assert_f32_near!(hit.charge_time(), 0.25);
+        assert_f32_near!(hit.shot_interval(), 0.25);
+    }
+
+    #[test]
+    fn progenitor_bonus() {
+        let impact = attack([(DamageType::Physical(Ips::Impact), 80.)].into());
+        let single_stat = |effect: ModEffect| Mod {
+            name: "Bonus Mod".to_owned(),
+            stats: vec![effect.into()],
+            ..Default::default()
+        };
+
+        // The bonus is 60% of base damage, and is scaled by Impact mods like
+        // the weapon's own Impact.
+        let hit = Hit::new(
+            &impact,
+            vec![single_stat(ModEffect::Physical(Ips::Impact, 1.))],
+            Enemy::new(Faction::Infested),
+        )
+        .with_progenitor(Some(Progenitor {
+            damage_type: DamageType::Physical(Ips::Impact),
+            bonus: 0.6,
+        }));
+        assert_f32_near!(hit.total_base(), 128.);
+        assert_f32_near!(hit.total_quantized(), 256.);
+
+        // Elemental bonuses combine after modded elements.
+        let impact = attack([(DamageType::Physical(Ips::Impact), 100.)].into());
+        let hit = Hit::new(
+            &impact,
+            vec![single_stat(ModEffect::Elemental(
+                Element::Primary(PrimaryElement::Cold),
+                1.,
+            ))],
+            Enemy::new(Faction::Infested),
+        )
+        .with_progenitor(Some(Progenitor {
+            damage_type: DamageType::Elemental(Element::Primary(PrimaryElement::Heat)),
+            bonus: 0.6,
+        }));
+        let blast = DamageType::Elemental(Element::Secondary(SecondaryElement::Blast));
+        assert_f32_near!(hit.contributions()[&blast], 160. + 60.);
+        assert_f32_near!(hit.total_quantized(), 100. + 220.);
+    }
+
+    #[test]
+    fn combination_order() {
+        let element = |name: &str, primary| Mod {
+            name: name.to_owned(),
+            stats: vec![ModEffect::Elemental(Element::Primary(primary), 0.9).into()],
+            ..Default::default()
+        };
+        let hellfire = element("Hellfire", PrimaryElement::Heat);
+        let cryo_rounds = element("Cryo Rounds", PrimaryElement::Cold);
+        let infected_clip = element("Infected Clip", PrimaryElement::Toxin);
+
+        let innate = attack(
+            [
+                (DamageType::Physical(Ips::Impact), 80.),
+                (
+                    DamageType::Elemental(Element::Primary(PrimaryElement::Electricity)),
+                    20.,
+                ),
+            ]
+            .into(),
+        );
+        let trace = |mods: Vec<Mod>| {
+            Hit::new(&innate, mods, Enemy::new(Faction::Grineer))
+                .combination_trace()
+                .into_iter()
+                .map(|c| (c.element, c.sources))
+                .collect::<Vec<_>>()
+        };
+
+        // The innate Electricity combines last, with whatever is left unpaired.
+        assert_eq!(
+            trace(vec![
+                hellfire.clone(),
+                cryo_rounds.clone(),
+                infected_clip.clone()
+            ]),
+            vec![
+                (
+                    Element::Secondary(SecondaryElement::Blast),
+                    vec!["Hellfire".to_owned(), "Cryo Rounds".to_owned()]
+                ),
+                (
+                    Element::Secondary(SecondaryElement::Corrosive),
+                    vec!["Infected Clip".to_owned(), INNATE.to_owned()]
+                ),
+            ]
+        );
+        assert_eq!(
+            trace(vec![cryo_rounds, infected_clip, hellfire]),
+            vec![
+                (
+                    Element::Secondary(SecondaryElement::Viral),
+                    vec!["Cryo Rounds".to_owned(), "Infected Clip".to_owned()]
+                ),
+                (
+                    Element::Secondary(SecondaryElement::Radiation),
+                    vec!["Hellfire".to_owned(), INNATE.to_owned()]
+                ),
+            ]
+        );
+    }
 }
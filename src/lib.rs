@@ -1,9 +1,20 @@
 #![feature(let_chains)]
 
+pub mod arcane;
+pub mod buff;
+pub mod build;
+pub mod critical;
 pub mod damage;
 pub mod enemy;
 pub mod hit;
 pub mod mods;
+pub mod optimizer;
+pub mod riven;
+pub mod simulation;
+pub mod stacking;
+pub mod status;
+pub mod survivability;
+pub mod warframe;
 pub mod weapon;
 
 pub mod public_export;
@@ -4,7 +4,15 @@ use std::{
     time::Duration,
 };
 
-use crate::{damage::*, enemy::Faction};
+use crate::{
+    damage::*,
+    enemy::Faction,
+    public_export::{
+        convert,
+        upgrade::{self, Upgrade},
+    },
+    weapon::*,
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -47,6 +55,7 @@ pub enum ModEffect {
     EnemyAccuracy(f32),
 
     //// Weapons
+    Damage(f32),
     Physical(Ips, f32),
     Elemental(Element, f32),
     Bane(Faction, f32),
@@ -59,6 +68,62 @@ pub enum ModEffect {
     CriticalDamage(f32),
     // Misc.
     Multishot(f32),
+    FireRate(f32),
+    ReloadSpeed(f32),
+    MagazineCapacity(f32),
+}
+
+impl ModEffect {
+    pub fn value_mut(&mut self) -> &mut f32 {
+        match self {
+            ModEffect::AbilityDuration(value)
+            | ModEffect::AbilityEfficiency(value)
+            | ModEffect::AbilityStrength(value)
+            | ModEffect::AbilityRange(value)
+            | ModEffect::HealthCapacity(value)
+            | ModEffect::HealthRegen(value)
+            | ModEffect::ShieldCapacity(value)
+            | ModEffect::ShieldRestore(value)
+            | ModEffect::ShieldRechargeRate(value)
+            | ModEffect::ShieldRechargeDelay(value)
+            | ModEffect::ShieldGateDuration(value)
+            | ModEffect::Armor(value)
+            | ModEffect::Energy(value)
+            | ModEffect::EnergyRegen(value)
+            | ModEffect::RadarEnemy(value)
+            | ModEffect::RadarLoot(value)
+            | ModEffect::SprintSpeed(value)
+            | ModEffect::Slide(value)
+            | ModEffect::Friction(value)
+            | ModEffect::DamageReduction(value)
+            | ModEffect::DamageRedirection(value)
+            | ModEffect::KnockdownResistance(value)
+            | ModEffect::PhysicalDamageResistance(value)
+            | ModEffect::EnvironmentalIceResistance(value)
+            | ModEffect::ElementResistance(_, value)
+            | ModEffect::EnemyAccuracy(value)
+            | ModEffect::Damage(value)
+            | ModEffect::Physical(_, value)
+            | ModEffect::Elemental(_, value)
+            | ModEffect::Bane(_, value)
+            | ModEffect::StatusChance(value)
+            | ModEffect::StatusDuration(value)
+            | ModEffect::StatusDamage(value)
+            | ModEffect::CriticalChance(value)
+            | ModEffect::CriticalDamage(value)
+            | ModEffect::Multishot(value)
+            | ModEffect::FireRate(value)
+            | ModEffect::ReloadSpeed(value)
+            | ModEffect::MagazineCapacity(value) => value,
+        }
+    }
+
+    /// The same effect with its value multiplied by `factor`, e.g. for stacks.
+    pub fn scaled(&self, factor: f32) -> Self {
+        let mut effect = self.clone();
+        *effect.value_mut() *= factor;
+        effect
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
@@ -108,6 +173,12 @@ pub struct ModStat {
     pub effect: ModEffect,
 }
 
+impl ModStat {
+    pub fn is_conditional(&self) -> bool {
+        self.trigger.is_some() || self.stacking.is_some()
+    }
+}
+
 impl From<ModEffect> for ModStat {
     fn from(effect: ModEffect) -> Self {
         Self {
@@ -118,10 +189,289 @@ impl From<ModEffect> for ModStat {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Clone, Copy)]
+pub enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Legendary,
+}
+
+impl Rarity {
+    /// Endo needed to fuse a mod of this rarity from rank 0 to rank 1. Each
+    /// following rank costs twice as much as the previous one.
+    pub fn base_fusion_cost(&self) -> u32 {
+        match self {
+            Rarity::Common => 10,
+            Rarity::Uncommon => 20,
+            Rarity::Rare => 30,
+            Rarity::Legendary => 40,
+        }
+    }
+
+    /// Credits needed to fuse a mod of this rarity from rank 0 to rank 1, as
+    /// listed in the in-game fusion screen. Following ranks double like endo.
+    pub fn base_fusion_credits(&self) -> u32 {
+        match self {
+            Rarity::Common => 483,
+            Rarity::Uncommon => 966,
+            Rarity::Rare => 1449,
+            Rarity::Legendary => 1932,
+        }
+    }
+}
+
+impl From<&upgrade::Rarity> for Rarity {
+    fn from(rarity: &upgrade::Rarity) -> Self {
+        match rarity {
+            upgrade::Rarity::Uncommon => Rarity::Uncommon,
+            upgrade::Rarity::Rare => Rarity::Rare,
+            upgrade::Rarity::Legendary => Rarity::Legendary,
+            upgrade::Rarity::Common | upgrade::Rarity::Error => Rarity::Common,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Polarity {
+    Madurai,
+    Vazarin,
+    Naramon,
+    Zenurik,
+    Unairu,
+    Penjaga,
+    Umbra,
+}
+
+impl Polarity {
+    /// Polarity of an exported upgrade, `None` for universal ones.
+    pub fn from_export(polarity: &upgrade::Polarity) -> Option<Self> {
+        match polarity {
+            upgrade::Polarity::Madurai => Some(Polarity::Madurai),
+            upgrade::Polarity::Vazarin => Some(Polarity::Vazarin),
+            upgrade::Polarity::Naramon => Some(Polarity::Naramon),
+            upgrade::Polarity::Zenurik => Some(Polarity::Zenurik),
+            upgrade::Polarity::Unairu => Some(Polarity::Unairu),
+            upgrade::Polarity::Penjaga => Some(Polarity::Penjaga),
+            upgrade::Polarity::Umbra => Some(Polarity::Umbra),
+            upgrade::Polarity::Universal | upgrade::Polarity::Error => None,
+        }
+    }
+}
+
+/// Prefixes and suffixes of weapon variants, such as Kuva Bramma and Braton
+/// Prime.
+const VARIANT_PREFIXES: &[&str] = &[
+    "Kuva ", "Tenet ", "Coda ", "Vaykor ", "Telos ", "Synoid ", "Rakta ", "Sancti ", "Secura ",
+    "Prisma ", "Dex ", "Mk1-",
+];
+const VARIANT_SUFFIXES: &[&str] = &[" Prime", " Vandal", " Wraith"];
+
+/// Name of the weapon a variant is based on, e.g. `Hek` for Vaykor Hek.
+fn base_name(name: &str) -> &str {
+    let name = VARIANT_PREFIXES
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .unwrap_or(name);
+    VARIANT_SUFFIXES
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .unwrap_or(name)
+}
+
+/// What a mod can be equipped on.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Clone)]
+pub enum Compatibility {
+    #[default]
+    Any,
+    /// Every primary weapon.
+    Primary,
+    /// Every primary weapon but shotguns.
+    Rifle,
+    Shotgun,
+    /// Bows and crossbows.
+    Bow,
+    Sniper,
+    /// Every secondary weapon.
+    Pistol,
+    Melee,
+    Archgun,
+    ArchMelee,
+    /// Augments of a single weapon and its variants, e.g. `Hek` for both Hek
+    /// and Vaykor Hek.
+    Weapon(String),
+    /// Mods for anything but weapons, such as Warframes, auras and stances,
+    /// by compat name.
+    Other(String),
+}
+
+impl Compatibility {
+    pub fn from_export(upgrade: &Upgrade) -> Self {
+        let compat_name = upgrade.compat_name.trim();
+        let weapon = || Compatibility::Weapon(compat_name.to_owned());
+
+        match (&upgrade.r#type, compat_name) {
+            (upgrade::Type::Primary, name) if name.eq_ignore_ascii_case("Primary") => {
+                Compatibility::Primary
+            }
+            (upgrade::Type::Primary, "Rifle" | "Assault Rifle") => Compatibility::Rifle,
+            (upgrade::Type::Primary, "Shotgun") => Compatibility::Shotgun,
+            (upgrade::Type::Primary, "Bow") => Compatibility::Bow,
+            (upgrade::Type::Primary, "Sniper") => Compatibility::Sniper,
+            (upgrade::Type::Primary, _) => weapon(),
+            (upgrade::Type::Secondary, "Pistol") => Compatibility::Pistol,
+            (upgrade::Type::Secondary, _) => weapon(),
+            (upgrade::Type::Melee, "Melee") => Compatibility::Melee,
+            (upgrade::Type::Melee, _) => weapon(),
+            (upgrade::Type::ArchGun, _) => Compatibility::Archgun,
+            (upgrade::Type::ArchMelee, _) => Compatibility::ArchMelee,
+            _ => Compatibility::Other(compat_name.to_owned()),
+        }
+    }
+
+    pub fn allows(&self, weapon: &Weapon) -> bool {
+        match self {
+            Compatibility::Any => true,
+            Compatibility::Weapon(name) => weapon.name == *name || base_name(&weapon.name) == name,
+            Compatibility::Other(_) => false,
+            class => Self::classes(&weapon.ty).contains(class),
+        }
+    }
+
+    /// Weapon class mods a weapon type accepts.
+    fn classes(ty: &WeaponType) -> Vec<Compatibility> {
+        use Compatibility::*;
+
+        match ty {
+            WeaponType::Primary(PrimaryWeaponType::Shotgun) => vec![Primary, Shotgun],
+            WeaponType::Primary(PrimaryWeaponType::Bow | PrimaryWeaponType::Crossbow) => {
+                vec![Primary, Rifle, Bow]
+            }
+            WeaponType::Primary(PrimaryWeaponType::SniperRifle) => vec![Primary, Rifle, Sniper],
+            WeaponType::Primary(_) => vec![Primary, Rifle],
+            WeaponType::Secondary(_) => vec![Pistol],
+            WeaponType::Melee(_) => vec![Melee],
+            WeaponType::Exalted(ty) => Self::classes(ty),
+            WeaponType::Archwing(ArchwingWeaponType::Melee) => vec![ArchMelee],
+            WeaponType::Archwing(_) => vec![Archgun],
+            WeaponType::Companion(CompanionWeaponType::Shotgun) => vec![Shotgun],
+            WeaponType::Companion(CompanionWeaponType::SniperRifle) => vec![Rifle, Sniper],
+            WeaponType::Companion(CompanionWeaponType::Rifle) => vec![Rifle],
+            WeaponType::Companion(CompanionWeaponType::Pistol) => vec![Pistol],
+            WeaponType::Companion(CompanionWeaponType::Glaive | CompanionWeaponType::Melee) => {
+                vec![Melee]
+            }
+            WeaponType::Modular(ModularWeaponType::Shotgun) => vec![Primary, Shotgun],
+            WeaponType::Modular(ModularWeaponType::Rifle | ModularWeaponType::Launcher) => {
+                vec![Primary, Rifle]
+            }
+            WeaponType::Modular(ModularWeaponType::Pistol) => vec![Pistol],
+            WeaponType::Modular(ModularWeaponType::Melee(_)) => vec![Melee],
+            WeaponType::Modular(ModularWeaponType::Amp) | WeaponType::Railjack(_) => vec![],
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
 pub struct Mod {
     pub name: String,
+    /// Stats at `max_rank`.
     pub stats: Vec<ModStat>,
+    /// Effects at every rank from 0 to `max_rank`, in the order of `stats`.
+    /// Empty for mods without a rank table, whose lower ranks are then
+    /// interpolated.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ranks: Vec<Vec<ModEffect>>,
+    #[serde(default)]
+    pub max_rank: u32,
+    /// Current rank, `None` meaning fully ranked.
+    #[serde(default)]
+    pub rank: Option<u32>,
+    #[serde(default)]
+    pub rarity: Rarity,
+    /// Capacity drain at rank 0.
+    #[serde(default)]
+    pub base_drain: u32,
+    #[serde(default)]
+    pub polarity: Option<Polarity>,
+    /// Mods of the same family, such as Serration and Amalgam Serration,
+    /// cannot be equipped together.
+    #[serde(default)]
+    pub family: Option<String>,
+    #[serde(default)]
+    pub compatibility: Compatibility,
+    /// Whether the mod fits the exilus slot.
+    #[serde(default)]
+    pub exilus: bool,
+}
+
+impl Mod {
+    pub fn rank(&self) -> u32 {
+        self.rank.unwrap_or(self.max_rank).min(self.max_rank)
+    }
+
+    pub fn with_rank(mut self, rank: u32) -> Self {
+        self.rank = Some(rank.min(self.max_rank));
+        self
+    }
+
+    /// Stats at the given rank, from the rank table if the mod has one.
+    /// Otherwise every rank adds the same share of the max rank value, so
+    /// rank 0 has `1 / (max_rank + 1)` of it.
+    pub fn stats_at(&self, rank: u32) -> Vec<ModStat> {
+        let rank = rank.min(self.max_rank);
+        if let Some(effects) = self.ranks.get(rank as usize)
+            && effects.len() == self.stats.len()
+        {
+            return self
+                .stats
+                .iter()
+                .zip(effects)
+                .map(|(stat, effect)| ModStat {
+                    effect: effect.clone(),
+                    ..stat.clone()
+                })
+                .collect();
+        }
+
+        let factor = (rank + 1) as f32 / (self.max_rank + 1) as f32;
+        self.stats
+            .iter()
+            .map(|stat| ModStat {
+                effect: stat.effect.scaled(factor),
+                ..stat.clone()
+            })
+            .collect()
+    }
+
+    /// Stats at the mod's current rank.
+    pub fn ranked_stats(&self) -> Vec<ModStat> {
+        self.stats_at(self.rank())
+    }
+
+    /// Capacity drain at the mod's current rank, before slot polarity.
+    pub fn drain(&self) -> u32 {
+        self.base_drain + self.rank()
+    }
+
+    /// Endo needed to fuse the mod from rank `from` to rank `to`.
+    pub fn fusion_endo(&self, from: u32, to: u32) -> u32 {
+        let (from, to) = (from.min(self.max_rank), to.min(self.max_rank));
+        if to <= from {
+            return 0;
+        }
+        self.rarity.base_fusion_cost() * ((1 << to) - (1 << from))
+    }
+
+    /// Credits needed to fuse the mod from rank `from` to rank `to`.
+    pub fn fusion_credits(&self, from: u32, to: u32) -> u32 {
+        let (from, to) = (from.min(self.max_rank), to.min(self.max_rank));
+        if to <= from {
+            return 0;
+        }
+        self.rarity.base_fusion_credits() * ((1 << to) - (1 << from))
+    }
 }
 
 #[derive(Debug)]
@@ -132,18 +482,70 @@ pub struct ModLibrary {
 
 impl ModLibrary {
     pub fn load(path: &Path) -> Self {
-        let file_content = std::fs::read_to_string(path).expect("Should read mod library file");
+        Self::try_load(path).expect("Should load mod library")
+    }
+
+    /// Like `load`, but returns an error instead of panicking if the file
+    /// cannot be read or deserialized.
+    pub fn try_load(path: &Path) -> Result<Self, String> {
+        let file_content = std::fs::read_to_string(path)
+            .map_err(|error| format!("Cannot read {}: {error}", path.display()))?;
         let mods = ron::from_str::<Vec<Mod>>(file_content.as_str())
-            .expect("Should deserialize mod library");
-        Self {
+            .map_err(|error| format!("Cannot deserialize {}: {error}", path.display()))?;
+        Ok(Self {
             path: path.to_path_buf(),
             mods: mods.into_iter().map(|m| (m.name.clone(), m)).collect(),
+        })
+    }
+
+    /// Builds a library at `path` from the Public Export's upgrades, along with
+    /// the level stats of each mod that could not be converted.
+    pub fn from_export(path: &Path, upgrades: &[Upgrade]) -> (Self, Vec<(String, Vec<String>)>) {
+        let mut unparsed = vec![];
+        let mut mods = HashMap::new();
+
+        for upgrade in upgrades {
+            let conversion = convert::convert(upgrade);
+            if !conversion.unparsed.is_empty() {
+                unparsed.push((upgrade.name.clone(), conversion.unparsed));
+            }
+            mods.insert(conversion.r#mod.name.clone(), conversion.r#mod);
         }
+
+        (
+            Self {
+                path: path.to_path_buf(),
+                mods,
+            },
+            unparsed,
+        )
+    }
+
+    pub fn save(&self) {
+        let mods = self.mods();
+        let text = ron::ser::to_string_pretty(&mods, ron::ser::PrettyConfig::default())
+            .expect("Mods should be ron-serializable");
+        std::fs::write(&self.path, text).expect("Should write mod library file");
     }
 
     pub fn get(&self, mod_name: &str) -> Option<&Mod> {
         self.mods.get(mod_name)
     }
+
+    /// Every mod in the library, sorted by name.
+    pub fn mods(&self) -> Vec<&Mod> {
+        let mut mods = self.mods.values().collect::<Vec<_>>();
+        mods.sort_by(|l, r| l.name.cmp(&r.name));
+        mods
+    }
+
+    /// The mods that can be equipped on `weapon`, sorted by name.
+    pub fn compatible(&self, weapon: &Weapon) -> Vec<&Mod> {
+        self.mods()
+            .into_iter()
+            .filter(|r#mod| r#mod.compatibility.allows(weapon))
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -160,17 +562,125 @@ mod test {
         let cryo_rounds = Mod {
             name: "Cryo Rounds".to_owned(),
             stats: vec![ModEffect::Elemental(Element::Primary(PrimaryElement::Cold), 0.9).into()],
+            ..Default::default()
         };
 
         let text = ron::ser::to_string_pretty(&cryo_rounds, ron::ser::PrettyConfig::default())
             .expect("Mods should be ron-serializable.");
 
-        std::fs::write("test.ron", text.as_str()).expect("Test file should be writable.");
-        let text = std::fs::read_to_string("test.ron").expect("Test file should be readable.");
-
         let parsed =
             ron::de::from_str::<Mod>(text.as_str()).expect("Mods should be ron-deserializable.");
 
         assert_eq!(cryo_rounds, parsed);
     }
+
+    #[test]
+    fn mod_ranks() {
+        let serration = Mod {
+            name: "Serration".to_owned(),
+            stats: vec![ModEffect::Damage(1.65).into()],
+            max_rank: 10,
+            ..Default::default()
+        };
+
+        assert_eq!(serration.rank(), 10);
+        assert_eq!(
+            serration.clone().with_rank(7).ranked_stats(),
+            vec![ModEffect::Damage(1.2).into()]
+        );
+        assert_eq!(serration.stats_at(0), vec![ModEffect::Damage(0.15).into()]);
+
+        assert_eq!(serration.fusion_endo(0, 10), 10_230);
+        assert_eq!(serration.fusion_credits(0, 10), 494_109);
+        assert_eq!(serration.fusion_endo(7, 10), 8_960);
+        assert_eq!(serration.fusion_endo(10, 3), 0);
+
+        // Rank tables are used as is, even when they don't grow linearly.
+        let uneven = Mod {
+            name: "Uneven".to_owned(),
+            stats: vec![ModEffect::AbilityEfficiency(0.3).into()],
+            ranks: [0.05, 0.1, 0.15, 0.22, 0.3]
+                .into_iter()
+                .map(|value| vec![ModEffect::AbilityEfficiency(value)])
+                .collect(),
+            max_rank: 4,
+            rarity: Rarity::Rare,
+            ..Default::default()
+        };
+        assert_eq!(
+            uneven.stats_at(3),
+            vec![ModEffect::AbilityEfficiency(0.22).into()]
+        );
+        assert_eq!(
+            uneven.stats_at(0),
+            vec![ModEffect::AbilityEfficiency(0.05).into()]
+        );
+        assert_eq!(uneven.fusion_credits(0, 4), 1449 * 15);
+    }
+
+    #[test]
+    fn compatibility() {
+        let weapons = WeaponLibrary::load(Path::new("data/weapons.ron"));
+        let braton_prime = weapons
+            .get("Braton Prime")
+            .expect("Braton Prime should exist");
+        let kuva_bramma = weapons
+            .get("Kuva Bramma")
+            .expect("Kuva Bramma should exist");
+
+        assert!(Compatibility::Rifle.allows(kuva_bramma));
+        assert!(Compatibility::Bow.allows(kuva_bramma));
+        assert!(!Compatibility::Bow.allows(braton_prime));
+        assert!(!Compatibility::Shotgun.allows(braton_prime));
+        assert!(Compatibility::Weapon("Bramma".to_owned()).allows(kuva_bramma));
+        assert!(!Compatibility::Weapon("Bramma".to_owned()).allows(braton_prime));
+        assert!(!Compatibility::Other("Warframe".to_owned()).allows(braton_prime));
+        assert!(!Compatibility::Weapon("Prime".to_owned()).allows(braton_prime));
+        assert!(!Compatibility::Weapon("Braton Prime Vandal".to_owned()).allows(braton_prime));
+
+        let library = ModLibrary::load(Path::new("data/mods.ron"));
+        assert_eq!(library.compatible(braton_prime).len(), library.mods().len());
+    }
+
+    #[test]
+    fn export_compatibility() {
+        let json = std::fs::read_to_string("data/public_export/ExportUpgrades_en.json")
+            .expect("Should read sample");
+        let export = serde_json::from_str::<upgrade::ExportUpgrades>(&json)
+            .expect("Should deserialize sample");
+        let (library, _) = ModLibrary::from_export(Path::new("mods.ron"), &export.export_upgrades);
+        let json = std::fs::read_to_string("data/public_export/ExportWeapons_en.json")
+            .expect("Should read sample");
+        let export = serde_json::from_str::<crate::public_export::weapon::ExportWeapons>(&json)
+            .expect("Should deserialize sample");
+        let weapons = WeaponLibrary::from_export(&export.export_weapons);
+        let get = |name: &str| weapons.get(name).expect("Weapon should exist");
+        let compatibility =
+            |name: &str| &library.get(name).expect("Mod should exist").compatibility;
+
+        assert_eq!(
+            compatibility("Vigilante Armaments"),
+            &Compatibility::Primary
+        );
+        assert_eq!(
+            compatibility("Scattered Justice"),
+            &Compatibility::Weapon("Hek".to_owned())
+        );
+        let names = |weapon: &str| {
+            library
+                .compatible(get(weapon))
+                .into_iter()
+                .map(|r#mod| r#mod.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names("Hek"),
+            vec!["Point Blank", "Scattered Justice", "Vigilante Armaments"]
+        );
+        assert_eq!(
+            names("Braton Prime"),
+            vec!["Serration", "Vigilante Armaments"]
+        );
+        assert!(names("Kuva Nukor").is_empty());
+    }
 }
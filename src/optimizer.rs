@@ -0,0 +1,565 @@
+use crate::{
+    build::{Loadout, Slot, SlotId},
+    damage::Element,
+    enemy::Enemy,
+    hit::Hit,
+    mods::{Mod, ModEffect, ModLibrary},
+    simulation,
+    stacking::Stacks,
+};
+
+/// Simulation runs per build when optimizing for time to kill.
+const TTK_RUNS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+    /// Damage per second while the magazine lasts.
+    Burst,
+    /// Damage per second including reloads and damage over time.
+    Sustained,
+    /// Mean simulated time to kill the optimizer's enemy, in seconds.
+    TimeToKill,
+}
+
+impl Objective {
+    fn is_better(&self, score: f32, other: f32) -> bool {
+        match self {
+            Objective::TimeToKill => score < other,
+            _ => score > other,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Build {
+    pub loadout: Loadout,
+    pub score: f32,
+}
+
+/// Beam search over mod combinations for the builds that score best on an
+/// `Objective`. Builds are grown one mod at a time and only the best
+/// `beam_width` of each size are expanded further.
+#[derive(Debug, Clone)]
+pub struct Optimizer {
+    template: Loadout,
+    attack: String,
+    enemy: Enemy,
+    objective: Objective,
+    candidates: Vec<Mod>,
+    beam_width: usize,
+    /// Stacks of conditional stats, every one at its maximum if `None`.
+    stacks: Option<Stacks>,
+}
+
+impl Optimizer {
+    /// The template's weapon, rank, catalyst and slot polarities are kept,
+    /// and its mods are replaced by those of the library that fit the weapon.
+    pub fn new(
+        template: Loadout,
+        attack: &str,
+        library: &ModLibrary,
+        enemy: Enemy,
+        objective: Objective,
+    ) -> Self {
+        Self {
+            candidates: library
+                .compatible(&template.weapon)
+                .into_iter()
+                .cloned()
+                .collect(),
+            template,
+            attack: attack.to_owned(),
+            enemy,
+            objective,
+            beam_width: 16,
+            stacks: None,
+        }
+    }
+
+    pub fn with_beam_width(mut self, beam_width: usize) -> Self {
+        self.beam_width = beam_width.max(1);
+        self
+    }
+
+    /// Scores every build with these stacks instead of the maximum ones, so
+    /// conditional stats only count as much as they are expected to be up.
+    pub fn with_stacks(mut self, stacks: Stacks) -> Self {
+        self.stacks = Some(stacks);
+        self
+    }
+
+    /// The `top` best builds found, best first.
+    pub fn search(&self, top: usize) -> Vec<Build> {
+        let mut found: Vec<(Vec<usize>, Build)> = vec![];
+        let mut beam: Vec<Vec<usize>> = vec![vec![]];
+
+        for _ in Loadout::slot_ids() {
+            let mut next: Vec<(Vec<usize>, Build)> = vec![];
+
+            for picked in beam.iter() {
+                // Candidates are only added in increasing order so every
+                // combination is reached once.
+                let first = picked.last().map(|last| last + 1).unwrap_or(0);
+                for candidate in first..self.candidates.len() {
+                    let mut indices = picked.clone();
+                    indices.push(candidate);
+                    if let Some(build) = self.evaluate(&indices) {
+                        next.push((indices, build));
+                    }
+                }
+            }
+
+            if next.is_empty() {
+                break;
+            }
+            self.sort(&mut next);
+            next.truncate(self.beam_width);
+
+            beam = next.iter().map(|(indices, _)| indices.clone()).collect();
+            found.extend(next);
+        }
+
+        self.sort(&mut found);
+        found.dedup_by(|(l, _), (r, _)| l == r);
+        found
+            .into_iter()
+            .take(top)
+            .map(|(_, build)| build)
+            .collect()
+    }
+
+    fn sort(&self, builds: &mut [(Vec<usize>, Build)]) {
+        builds.sort_by(|(_, l), (_, r)| {
+            if self.objective.is_better(l.score, r.score) {
+                std::cmp::Ordering::Less
+            } else if self.objective.is_better(r.score, l.score) {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        });
+    }
+
+    /// Scores the mods in the slots that fit them best. Elements combine in
+    /// the order they first appear in, so every order of the mods' elements
+    /// is tried.
+    fn evaluate(&self, indices: &[usize]) -> Option<Build> {
+        let mods = indices
+            .iter()
+            .map(|index| self.candidates[*index].clone())
+            .collect::<Vec<_>>();
+        let mut elements = vec![];
+        for element in mods.iter().filter_map(first_element) {
+            if !elements.contains(&element) {
+                elements.push(element);
+            }
+        }
+
+        permutations(&elements)
+            .into_iter()
+            .filter_map(|order| {
+                let loadout = self.fit(&mods, &order)?;
+                let hit = loadout.hit(&self.attack, self.enemy.clone())?;
+                let stacks = self
+                    .stacks
+                    .clone()
+                    .unwrap_or_else(|| Stacks::max(hit.mods()));
+                let score = self.score(&hit.with_stacks(stacks))?;
+                Some(Build { loadout, score })
+            })
+            .reduce(|best, build| {
+                if self.objective.is_better(build.score, best.score) {
+                    build
+                } else {
+                    best
+                }
+            })
+    }
+
+    /// Places the mods in the template's slots, exilus included, so they
+    /// drain the least while their elements first appear in `order`, or
+    /// `None` if they don't fit. Slots are filled in order, keeping the least
+    /// draining placement for each set of placed mods and number of elements
+    /// seen.
+    fn fit(&self, mods: &[Mod], order: &[Element]) -> Option<Loadout> {
+        let mut loadout = self.template.clone();
+        let ids = Loadout::slot_ids().collect::<Vec<_>>();
+        for id in ids.iter() {
+            loadout.unequip(*id).ok()?;
+        }
+
+        // Drain of each mod in each slot, `None` where it cannot go.
+        let drains = mods
+            .iter()
+            .map(|r#mod| {
+                ids.iter()
+                    .map(|id| {
+                        if *id == SlotId::Exilus && !r#mod.exilus {
+                            return None;
+                        }
+                        let slot = Slot {
+                            r#mod: Some(r#mod.clone()),
+                            polarity: loadout.slot(*id).ok()?.polarity,
+                        };
+                        Some(slot.drain())
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let positions = mods
+            .iter()
+            .map(|r#mod| {
+                let element = first_element(r#mod)?;
+                order.iter().position(|other| *other == element)
+            })
+            .collect::<Vec<_>>();
+
+        // Least draining placement, by placed mods and elements seen.
+        let seen_states = order.len() + 1;
+        let mut placements: Vec<Option<Placement>> = vec![None; (1 << mods.len()) * seen_states];
+        placements[0] = Some((0, vec![]));
+        for slot in 0..ids.len() {
+            // Leaving the slot empty keeps every placement.
+            let mut next = placements.clone();
+            for (state, placement) in placements.iter().enumerate() {
+                let Some((drain, placed)) = placement else {
+                    continue;
+                };
+                let (taken, seen) = (state / seen_states, state % seen_states);
+                for (index, drains) in drains.iter().enumerate() {
+                    let Some(slot_drain) = drains[slot] else {
+                        continue;
+                    };
+                    if taken & (1 << index) != 0 {
+                        continue;
+                    }
+                    let seen = match positions[index] {
+                        Some(position) if position > seen => continue,
+                        Some(position) if position == seen => seen + 1,
+                        _ => seen,
+                    };
+                    let drain = drain + slot_drain;
+                    let best = &mut next[(taken | (1 << index)) * seen_states + seen];
+                    if best.as_ref().is_none_or(|(best, _)| drain < *best) {
+                        let mut placed = placed.clone();
+                        placed.push((index, slot));
+                        *best = Some((drain, placed));
+                    }
+                }
+            }
+            placements = next;
+        }
+
+        let all = (1 << mods.len()) - 1;
+        let (_, placed) = placements[all * seen_states + order.len()].clone()?;
+        for (index, slot) in placed {
+            loadout.equip(ids[slot], mods[index].clone()).ok()?;
+        }
+
+        loadout.validate().ok()?;
+        Some(loadout)
+    }
+
+    fn score(&self, hit: &Hit) -> Option<f32> {
+        match self.objective {
+            Objective::Burst => Some(hit.burst_dps()),
+            Objective::Sustained => Some(hit.sustained_dps()),
+            Objective::TimeToKill => simulation::time_to_kill(hit, TTK_RUNS, 0).map(|ttk| ttk.mean),
+        }
+    }
+}
+
+/// Drain of a partial placement and its (mod, slot) pairs.
+type Placement = (u32, Vec<(usize, usize)>);
+
+/// Element of a mod's first elemental stat, which decides where the mod's
+/// elements combine.
+fn first_element(r#mod: &Mod) -> Option<Element> {
+    r#mod.stats.iter().find_map(|stat| match stat.effect {
+        ModEffect::Elemental(element, _) => Some(element),
+        _ => None,
+    })
+}
+
+/// Every order of `items`.
+fn permutations<T: Copy>(items: &[T]) -> Vec<Vec<T>> {
+    if items.is_empty() {
+        return vec![vec![]];
+    }
+    (0..items.len())
+        .flat_map(|index| {
+            let mut rest = items.to_vec();
+            let item = rest.remove(index);
+            permutations(&rest).into_iter().map(move |mut order| {
+                order.insert(0, item);
+                order
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        build::MOD_SLOTS,
+        damage::*,
+        enemy::Faction,
+        mods::{ModEffect, ModStat, Polarity},
+        weapon::{Attack, PrimaryWeaponType, Weapon, WeaponType},
+    };
+
+    use std::{collections::HashMap, path::Path};
+
+    fn template() -> Loadout {
+        Loadout::new(Weapon {
+            name: "Braton".to_owned(),
+            mastery_rank: 0,
+            ty: WeaponType::Primary(PrimaryWeaponType::Rifle),
+            attacks: vec![Attack {
+                name: "Normal Attack".to_owned(),
+                base_damage: HashMap::from([(DamageType::Physical(Ips::Impact), 20.)]),
+                critical_chance: 0.1,
+                critical_multiplier: 2.,
+                status_chance: 0.1,
+                fire_rate: 10.,
+                charge_time: 0.,
+                multishot: 1.,
+                magazine: 45,
+                reload: 2.,
+                ammo: None,
+            }],
+            progenitor: None,
+        })
+    }
+
+    #[test]
+    fn best_builds() {
+        let library = ModLibrary::load(Path::new("data/mods.ron"));
+        let mut template = template();
+        template
+            .set_polarity(SlotId::Mod(0), Some(Polarity::Madurai))
+            .expect("Should polarize");
+
+        let builds = Optimizer::new(
+            template.clone(),
+            "Normal Attack",
+            &library,
+            Enemy::new(Faction::Grineer),
+            Objective::Burst,
+        )
+        .search(3);
+
+        assert_eq!(builds.len(), 3);
+        assert!(builds.windows(2).all(|w| w[0].score >= w[1].score));
+        let full = Optimizer::new(
+            template.clone(),
+            "Normal Attack",
+            &library,
+            Enemy::new(Faction::Grineer),
+            Objective::Burst,
+        )
+        .search(10);
+        // Every combination fits once Galvanized Chamber takes the Madurai slot.
+        assert_eq!(full.len(), 7);
+        let all = full
+            .iter()
+            .find(|build| build.loadout.mods().len() == 3)
+            .expect("All three mods should fit");
+        assert_eq!(
+            all.loadout
+                .slot(SlotId::Mod(0))
+                .expect("Slot should exist")
+                .r#mod
+                .as_ref()
+                .map(|m| m.name.as_str()),
+            Some("Galvanized Chamber")
+        );
+
+        // At a lower rank, both elemental mods are the only pair that fits,
+        // and the Viral stacks they keep up outweigh Galvanized Chamber's
+        // multishot.
+        let builds = Optimizer::new(
+            template.clone().with_rank(12),
+            "Normal Attack",
+            &library,
+            Enemy::new(Faction::Grineer),
+            Objective::Burst,
+        )
+        .search(10);
+        assert_eq!(builds.len(), 4);
+        let names = |build: &Build| {
+            let mut names = build
+                .loadout
+                .mods()
+                .into_iter()
+                .map(|m| m.name)
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+        assert_eq!(names(&builds[0]), vec!["Cryo Rounds", "Malignant Force"]);
+        let chamber = |builds: &[Build]| {
+            builds
+                .iter()
+                .find(|build| names(build) == ["Galvanized Chamber"])
+                .expect("Galvanized Chamber should fit alone")
+                .score
+        };
+
+        // Without kills, Galvanized Chamber's conditional multishot is off.
+        let unstacked = Optimizer::new(
+            template.with_rank(12),
+            "Normal Attack",
+            &library,
+            Enemy::new(Faction::Grineer),
+            Objective::Burst,
+        )
+        .with_stacks(Stacks::default())
+        .search(10);
+        assert!(chamber(&unstacked) < chamber(&builds));
+    }
+
+    #[test]
+    fn element_order() {
+        let element = |name: &str, primary| Mod {
+            name: name.to_owned(),
+            stats: vec![ModStat::from(ModEffect::Elemental(
+                Element::Primary(primary),
+                0.9,
+            ))],
+            ..Default::default()
+        };
+        let mods = vec![
+            element("Heat", PrimaryElement::Heat),
+            element("Cold", PrimaryElement::Cold),
+            element("Toxin", PrimaryElement::Toxin),
+        ];
+        let optimizer = Optimizer {
+            candidates: mods.clone(),
+            ..Optimizer::new(
+                template(),
+                "Normal Attack",
+                &ModLibrary::load(Path::new("data/mods.ron")),
+                Enemy::new(Faction::Corrupted),
+                Objective::Burst,
+            )
+        };
+
+        // In library order, Heat and Cold form Blast and Toxin stays apart.
+        let mut in_order = template();
+        for (index, r#mod) in mods.into_iter().enumerate() {
+            in_order
+                .equip(SlotId::Mod(index), r#mod)
+                .expect("Should equip");
+        }
+        let hit = in_order
+            .hit("Normal Attack", Enemy::new(Faction::Corrupted))
+            .expect("Braton should have a normal attack");
+        let elements = |hit: &Hit| {
+            hit.combination_trace()
+                .into_iter()
+                .map(|combination| combination.element)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            elements(&hit),
+            vec![
+                Element::Secondary(SecondaryElement::Blast),
+                Element::Primary(PrimaryElement::Toxin)
+            ]
+        );
+
+        // Corrupted health is weak to Viral, so Cold and Toxin go first.
+        let best = &optimizer.search(1)[0];
+        let best_hit = best
+            .loadout
+            .hit("Normal Attack", Enemy::new(Faction::Corrupted))
+            .expect("Braton should have a normal attack");
+        assert!(elements(&best_hit).contains(&Element::Secondary(SecondaryElement::Viral)));
+        assert!(best.score > optimizer.score(&hit).expect("Burst DPS should be scored"));
+    }
+
+    #[test]
+    fn slot_assignment() {
+        let r#mod = |name: &str, base_drain: u32, polarity: Option<Polarity>| Mod {
+            name: name.to_owned(),
+            stats: vec![ModStat::from(ModEffect::Damage(1.))],
+            base_drain,
+            polarity,
+            ..Default::default()
+        };
+        let mut template = template();
+        template
+            .set_polarity(SlotId::Mod(0), Some(Polarity::Madurai))
+            .expect("Should polarize");
+        for index in 1..MOD_SLOTS {
+            template
+                .set_polarity(SlotId::Mod(index), Some(Polarity::Vazarin))
+                .expect("Should polarize");
+        }
+        let optimizer = Optimizer {
+            // Placing the most draining mod first in its cheapest slot takes
+            // the Madurai slot and leaves the exilus slot empty, draining
+            // 15 + 13 + 13, over capacity.
+            candidates: vec![
+                r#mod("Unpolarized", 12, None),
+                r#mod("Madurai", 10, Some(Polarity::Madurai)),
+                Mod {
+                    exilus: true,
+                    ..r#mod("Exilus", 10, None)
+                },
+            ],
+            ..Optimizer::new(
+                template,
+                "Normal Attack",
+                &ModLibrary::load(Path::new("data/mods.ron")),
+                Enemy::new(Faction::Grineer),
+                Objective::Burst,
+            )
+        };
+
+        let builds = optimizer.search(1);
+        let loadout = &builds[0].loadout;
+        assert_eq!(loadout.mods().len(), 3);
+        assert_eq!(loadout.drain(), 15 + 5 + 10);
+        let name = |id| {
+            loadout
+                .slot(id)
+                .expect("Slot should exist")
+                .r#mod
+                .as_ref()
+                .map(|m| m.name.clone())
+        };
+        assert_eq!(name(SlotId::Mod(0)), Some("Madurai".to_owned()));
+        assert_eq!(name(SlotId::Exilus), Some("Exilus".to_owned()));
+    }
+
+    #[test]
+    fn family_exclusivity() {
+        let serration = |name: &str, damage: f32| Mod {
+            name: name.to_owned(),
+            stats: vec![ModStat::from(ModEffect::Damage(damage))],
+            family: Some("Serration".to_owned()),
+            ..Default::default()
+        };
+        let template = template();
+        let optimizer = Optimizer {
+            candidates: vec![
+                serration("Serration", 1.65),
+                serration("Amalgam Serration", 1.55),
+            ],
+            ..Optimizer::new(
+                template,
+                "Normal Attack",
+                &ModLibrary::load(Path::new("data/mods.ron")),
+                Enemy::new(Faction::Grineer),
+                Objective::Sustained,
+            )
+        };
+
+        let builds = optimizer.search(5);
+        assert_eq!(builds.len(), 2);
+        assert_eq!(builds[0].loadout.mods()[0].name, "Serration");
+    }
+}
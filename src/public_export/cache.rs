@@ -0,0 +1,190 @@
+use std::{
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+};
+
+use serde::de::DeserializeOwned;
+
+/// An entry of the Public Export index, such as
+/// `ExportUpgrades_en.json!00_dGVzdA`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Manifest {
+    pub file_name: String,
+    pub hash: String,
+}
+
+impl Manifest {
+    pub fn parse(line: &str) -> Option<Self> {
+        let (file_name, hash) = line.trim().split_once('!')?;
+        Some(Self {
+            file_name: file_name.to_owned(),
+            hash: hash.to_owned(),
+        })
+    }
+
+    /// Name of the manifest's category, e.g. `Upgrades` for
+    /// `ExportUpgrades_en.json`.
+    pub fn category(&self) -> &str {
+        let name = self
+            .file_name
+            .strip_prefix("Export")
+            .unwrap_or(&self.file_name);
+        name.split(['_', '.']).next().unwrap_or(name)
+    }
+
+    /// The index line the manifest is downloaded from.
+    pub fn line(&self) -> String {
+        format!("{}!{}", self.file_name, self.hash)
+    }
+}
+
+pub fn parse_index(index: &str) -> Vec<Manifest> {
+    index.lines().filter_map(Manifest::parse).collect()
+}
+
+/// Decompresses the LZMA-compressed `index_<lang>.txt.lzma`.
+pub fn decompress_index(compressed: &[u8]) -> io::Result<String> {
+    let mut index = vec![];
+    lzma_rs::lzma_decompress(&mut BufReader::new(compressed), &mut index)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))?;
+    String::from_utf8(index).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+/// Local mirror of the Public Export. Every manifest is stored under its
+/// category and hash, so unchanged manifests are never downloaded twice, and
+/// the index of the last complete sync says which hash is current.
+pub struct Cache {
+    dir: PathBuf,
+}
+
+const INDEX: &str = "index.txt";
+
+impl Cache {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    pub fn manifest_path(&self, manifest: &Manifest) -> PathBuf {
+        self.dir
+            .join(manifest.category())
+            .join(format!("{}.json", encode_hash(&manifest.hash)))
+    }
+
+    pub fn contains(&self, manifest: &Manifest) -> bool {
+        self.manifest_path(manifest).is_file()
+    }
+
+    /// Writes a manifest, going through a temporary file so an interrupted
+    /// sync never leaves a partial manifest behind.
+    pub fn store(&self, manifest: &Manifest, content: &[u8]) -> io::Result<()> {
+        let path = self.manifest_path(manifest);
+        write_atomic(&path, content)
+    }
+
+    /// Records the index of a complete sync.
+    pub fn save_index(&self, manifests: &[Manifest]) -> io::Result<()> {
+        let index = manifests
+            .iter()
+            .map(|manifest| manifest.line() + "\n")
+            .collect::<String>();
+        write_atomic(&self.dir.join(INDEX), index.as_bytes())
+    }
+
+    pub fn index(&self) -> Option<Vec<Manifest>> {
+        let index = std::fs::read_to_string(self.dir.join(INDEX)).ok()?;
+        Some(parse_index(&index))
+    }
+
+    /// Content of the current manifest of a category, e.g. `Upgrades`.
+    pub fn load(&self, category: &str) -> Option<String> {
+        let manifest = self
+            .index()?
+            .into_iter()
+            .find(|manifest| manifest.category() == category)?;
+        std::fs::read_to_string(self.manifest_path(&manifest)).ok()
+    }
+
+    /// Deserializes the current manifest of a category, e.g. `ExportWeapons`
+    /// from `Weapons`.
+    pub fn load_export<T: DeserializeOwned>(&self, category: &str) -> Result<T, String> {
+        let content = self
+            .load(category)
+            .ok_or(format!("No cached {category} manifest"))?;
+        serde_json::from_str(&content)
+            .map_err(|error| format!("Invalid {category} manifest: {error}"))
+    }
+}
+
+/// Percent-encodes a hash for use as a file name. Hashes are base64 and may
+/// contain slashes, and unlike replacing them this never maps two hashes to
+/// the same name.
+fn encode_hash(hash: &str) -> String {
+    hash.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let partial = path.with_extension("partial");
+    std::fs::write(&partial, content)?;
+    std::fs::rename(&partial, path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn index() {
+        let text = "ExportUpgrades_en.json!00_dGVz/dA\r\nExportWeapons_en.json!00_d2Vh\n";
+        let mut compressed = vec![];
+        lzma_rs::lzma_compress(&mut BufReader::new(text.as_bytes()), &mut compressed)
+            .expect("Should compress");
+
+        let manifests = parse_index(&decompress_index(&compressed).expect("Should decompress"));
+        assert_eq!(manifests.len(), 2);
+        assert_eq!(manifests[0].category(), "Upgrades");
+        assert_eq!(manifests[0].hash, "00_dGVz/dA");
+        assert_eq!(manifests[1].line(), "ExportWeapons_en.json!00_d2Vh");
+
+        assert_eq!(encode_hash("00_dGVz/dA+"), "00_dGVz%2FdA%2B");
+        assert_ne!(encode_hash("a/b"), encode_hash("a_b"));
+    }
+
+    #[test]
+    fn cache() {
+        let dir = std::env::temp_dir().join("wfcalc_public_export_cache");
+        let _ = std::fs::remove_dir_all(&dir);
+        let cache = Cache::new(&dir);
+        let manifest = Manifest::parse("ExportUpgrades_en.json!00_dGVz/dA").unwrap();
+
+        assert!(!cache.contains(&manifest));
+        assert_eq!(cache.load("Upgrades"), None);
+
+        cache
+            .store(&manifest, b"{}")
+            .expect("Should store manifest");
+        cache
+            .save_index(std::slice::from_ref(&manifest))
+            .expect("Should save index");
+        assert!(cache.contains(&manifest));
+        assert_eq!(cache.load("Upgrades").as_deref(), Some("{}"));
+        assert!(
+            cache
+                .load_export::<crate::public_export::upgrade::ExportUpgrades>("Upgrades")
+                .is_err()
+        );
+
+        std::fs::remove_dir_all(&dir).expect("Should clean up");
+    }
+}
@@ -0,0 +1,667 @@
+use crate::{
+    arcane::{Arcane, ArcaneSlot},
+    damage::*,
+    enemy::Faction,
+    mods::{
+        self, Compatibility, Mod, ModEffect, ModStat, Stacking, StackingBehaviour,
+        TimeoutBehaviour, Trigger,
+    },
+    public_export::{
+        relic_arcane::RelicArcane,
+        upgrade::Upgrade,
+        weapon::{self as export, ProductCategory, Trigger as ExportTrigger},
+    },
+    weapon::*,
+};
+
+use std::{collections::HashMap, time::Duration};
+
+/// An `Upgrade` converted to a `Mod` with its rank table, with the level stats
+/// that could not be understood.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Conversion {
+    pub r#mod: Mod,
+    pub unparsed: Vec<String>,
+}
+
+pub fn convert(upgrade: &Upgrade) -> Conversion {
+    let mut unparsed = vec![];
+
+    let ranks = upgrade
+        .level_stats
+        .iter()
+        .map(|level| {
+            level
+                .stats
+                .iter()
+                .flat_map(|stats| stats.lines())
+                .filter(|line| !line.trim().is_empty())
+                .filter_map(|line| {
+                    let effect = parse_level_stat(line);
+                    if effect.is_none() && !unparsed.iter().any(|u| u == line.trim()) {
+                        unparsed.push(line.trim().to_owned());
+                    }
+                    effect
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    Conversion {
+        r#mod: Mod {
+            name: upgrade.name.clone(),
+            stats: ranks
+                .last()
+                .into_iter()
+                .flatten()
+                .cloned()
+                .map(ModStat::from)
+                .collect(),
+            ranks,
+            max_rank: upgrade.fusion_limit,
+            rank: None,
+            rarity: (&upgrade.rarity).into(),
+            base_drain: upgrade.base_drain,
+            polarity: mods::Polarity::from_export(&upgrade.polarity),
+            family: None,
+            compatibility: mods::Compatibility::from_export(upgrade),
+            exilus: upgrade.is_utility,
+        },
+        unparsed,
+    }
+}
+
+/// Parses a single level stat line such as `+90% <DT_FREEZE_COLOR>Cold` or
+/// `+30% Damage to Grineer`.
+pub fn parse_level_stat(line: &str) -> Option<ModEffect> {
+    let line = line.trim();
+    let (value, stat) = line.split_once('%')?;
+    let value = value.trim().parse::<f32>().ok()? / 100.;
+    let stat = stat.trim();
+
+    if let Some(tagged) = stat.strip_prefix('<') {
+        let (tag, name) = tagged.split_once('>')?;
+        let damage_type = parse_damage_tag(tag)?;
+
+        return match (damage_type, name.trim().strip_suffix("Resistance")) {
+            (DamageType::Elemental(element), Some(_)) => {
+                Some(ModEffect::ElementResistance(element, value))
+            }
+            (DamageType::Physical(ips), None) => Some(ModEffect::Physical(ips, value)),
+            (DamageType::Elemental(element), None) => Some(ModEffect::Elemental(element, value)),
+            _ => None,
+        };
+    }
+
+    if let Some(faction) = stat.strip_prefix("Damage to ") {
+        return parse_faction(faction).map(|faction| ModEffect::Bane(faction, value));
+    }
+
+    let effect = match stat {
+        "Damage" | "Melee Damage" => ModEffect::Damage,
+        "Critical Chance" => ModEffect::CriticalChance,
+        "Critical Damage" => ModEffect::CriticalDamage,
+        "Status Chance" => ModEffect::StatusChance,
+        "Status Duration" => ModEffect::StatusDuration,
+        "Status Damage" => ModEffect::StatusDamage,
+        "Multishot" => ModEffect::Multishot,
+        "Fire Rate" | "Fire Rate (x2 for Bows)" | "Attack Speed" => ModEffect::FireRate,
+        "Reload Speed" => ModEffect::ReloadSpeed,
+        "Magazine Capacity" => ModEffect::MagazineCapacity,
+        "Ability Duration" => ModEffect::AbilityDuration,
+        "Ability Efficiency" => ModEffect::AbilityEfficiency,
+        "Ability Strength" => ModEffect::AbilityStrength,
+        "Ability Range" => ModEffect::AbilityRange,
+        "Health" => ModEffect::HealthCapacity,
+        "Health Regen" => ModEffect::HealthRegen,
+        "Shield Capacity" => ModEffect::ShieldCapacity,
+        "Shield Recharge" => ModEffect::ShieldRechargeRate,
+        "Armor" => ModEffect::Armor,
+        "Energy Max" => ModEffect::Energy,
+        "Sprint Speed" => ModEffect::SprintSpeed,
+        "Enemy Radar" => ModEffect::RadarEnemy,
+        "Loot Radar" => ModEffect::RadarLoot,
+        "Slide" => ModEffect::Slide,
+        "Friction" => ModEffect::Friction,
+        "Damage Resistance" => ModEffect::DamageReduction,
+        "Knockdown Resistance" => ModEffect::KnockdownResistance,
+        _ => return None,
+    };
+
+    Some(effect(value))
+}
+
+/// Parses damage type tags such as `DT_FIRE` or `DT_FIRE_COLOR`.
+fn parse_damage_tag(tag: &str) -> Option<DamageType> {
+    use DamageType::*;
+    use Element::*;
+    use PrimaryElement::*;
+    use SecondaryElement::*;
+
+    let tag = tag.strip_suffix("_COLOR").unwrap_or(tag);
+    Some(match tag {
+        "DT_IMPACT" => Physical(Ips::Impact),
+        "DT_PUNCTURE" => Physical(Ips::Puncture),
+        "DT_SLASH" => Physical(Ips::Slash),
+        "DT_FIRE" => Elemental(Primary(Heat)),
+        "DT_FREEZE" => Elemental(Primary(Cold)),
+        "DT_ELECTRICITY" => Elemental(Primary(Electricity)),
+        "DT_POISON" => Elemental(Primary(Toxin)),
+        "DT_EXPLOSION" => Elemental(Secondary(Blast)),
+        "DT_RADIATION" => Elemental(Secondary(Radiation)),
+        "DT_GAS" => Elemental(Secondary(Gas)),
+        "DT_MAGNETIC" => Elemental(Secondary(Magnetic)),
+        "DT_VIRAL" => Elemental(Secondary(Viral)),
+        "DT_CORROSIVE" => Elemental(Secondary(Corrosive)),
+        _ => return None,
+    })
+}
+
+fn parse_faction(faction: &str) -> Option<Faction> {
+    Some(match faction.trim() {
+        "Grineer" => Faction::Grineer,
+        "Corpus" => Faction::Corpus,
+        "Corrupted" | "Orokin" => Faction::Corrupted,
+        "Infested" => Faction::Infested,
+        "Murmur" | "The Murmur" => Faction::Murmur,
+        _ => return None,
+    })
+}
+
+/// Converts an exported arcane with the stats of every rank, along with the
+/// level stats that could not be understood. Arcanes for anything but weapons
+/// and Warframes, such as operator, amp, Zaw and Kitgun arcanes, are not
+/// supported and give `None`.
+pub fn convert_arcane(item: &RelicArcane) -> Option<(Arcane, Vec<String>)> {
+    let (slot, compatibility) = match item.name.split_once(' ')? {
+        ("Primary", _) => (ArcaneSlot::Weapon, Compatibility::Primary),
+        ("Secondary", _) => (ArcaneSlot::Weapon, Compatibility::Pistol),
+        ("Melee", _) => (ArcaneSlot::Weapon, Compatibility::Melee),
+        ("Arcane" | "Molt" | "Cascadia", _) => (
+            ArcaneSlot::Warframe,
+            Compatibility::Other("Warframe".to_owned()),
+        ),
+        _ => return None,
+    };
+
+    let mut unparsed = vec![];
+    let ranks = item
+        .level_stats
+        .iter()
+        .map(|level| {
+            // `None` while under a trigger that is not understood.
+            let mut trigger = Some(None);
+            level
+                .stats
+                .iter()
+                .flat_map(|stats| stats.lines())
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .filter_map(|line| {
+                    if let Some(condition) = line.strip_suffix(':') {
+                        trigger = parse_trigger(condition).map(Some);
+                        if trigger.is_none() && !unparsed.iter().any(|u| u == line) {
+                            unparsed.push(line.to_owned());
+                        }
+                        return None;
+                    }
+                    let stat = trigger
+                        .clone()
+                        .and_then(|trigger| parse_arcane_stat(line, trigger));
+                    if stat.is_none() && !unparsed.iter().any(|u| u == line) {
+                        unparsed.push(line.to_owned());
+                    }
+                    stat
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let arcane = Arcane {
+        name: item.name.clone(),
+        slot,
+        compatibility,
+        stats: ranks.last().cloned().unwrap_or_default(),
+        ranks: ranks
+            .into_iter()
+            .map(|stats| stats.into_iter().map(|stat| stat.effect).collect())
+            .collect(),
+        max_rank: item.level_stats.len().saturating_sub(1) as u32,
+        rank: None,
+        rarity: item.rarity.as_ref().map(Into::into).unwrap_or_default(),
+    };
+    Some((arcane, unparsed))
+}
+
+/// Parses arcane conditions such as `On Headshot Kill`.
+fn parse_trigger(condition: &str) -> Option<Trigger> {
+    Some(match condition.trim() {
+        "On Kill" => Trigger::Kill,
+        "On Headshot Kill" => Trigger::WeakpointKill,
+        "On Headshot" => Trigger::WeakpointHit,
+        "On Hit" => Trigger::Hit,
+        "On Damaged" => Trigger::Damaged,
+        "On Energy Pickup" => Trigger::EnergyOrbPickup,
+        "On Health Pickup" | "On Health Orb Pickup" => Trigger::HealthOrbPickup,
+        "On Ammo Pickup" => Trigger::AmmoPickup,
+        _ => return None,
+    })
+}
+
+/// Parses an arcane stat such as `+30% Primary Damage for 24s`, optionally
+/// followed by `. Stacks up to 12x.`
+fn parse_arcane_stat(line: &str, trigger: Option<Trigger>) -> Option<ModStat> {
+    let (stat, rest) = line.split_once(" for ").unwrap_or((line, ""));
+    let stat = ["Primary", "Secondary", "Melee"]
+        .into_iter()
+        .fold(stat.trim_end_matches('.').to_owned(), |stat, class| {
+            stat.replacen(&format!("% {class} "), "% ", 1)
+        });
+    let effect = parse_level_stat(&stat)?;
+
+    let duration = rest
+        .split_once('s')
+        .and_then(|(seconds, _)| seconds.trim().parse::<f32>().ok());
+    let max = line
+        .split_once("Stacks up to ")
+        .and_then(|(_, stacks)| stacks.split_once('x'))
+        .and_then(|(stacks, _)| stacks.trim().parse::<u32>().ok());
+
+    let stacking = match (duration, max) {
+        (None, None) => None,
+        (duration, max) => Some(Stacking {
+            max: max.unwrap_or(1),
+            behaviour: match duration {
+                Some(duration) => StackingBehaviour::Timed {
+                    duration: Duration::from_secs_f32(duration),
+                    timeout: TimeoutBehaviour::Drop,
+                    resets_on_stack: true,
+                },
+                None => StackingBehaviour::Forever,
+            },
+        }),
+    };
+
+    Some(ModStat {
+        trigger,
+        stacking,
+        effect,
+    })
+}
+
+/// Converts an exported weapon, or returns `None` if it is not a weapon that
+/// can be equipped, such as a modular weapon part.
+pub fn convert_weapon(weapon: &export::Weapon) -> Option<Weapon> {
+    let ty = weapon_type(weapon)?;
+
+    let base_damage = weapon
+        .damage_per_shot
+        .iter()
+        .enumerate()
+        .filter(|(_, damage)| **damage > 0.)
+        .filter_map(|(index, damage)| Some((export_damage_type(index)?, *damage)))
+        .collect::<HashMap<_, _>>();
+
+    let normal = Attack {
+        name: "Normal Attack".to_owned(),
+        base_damage,
+        critical_chance: weapon.critical_chance,
+        critical_multiplier: weapon.critical_multiplier,
+        status_chance: weapon.proc_chance,
+        fire_rate: weapon.fire_rate,
+        // Charged weapons list the inverse of their charge time as fire rate.
+        charge_time: match weapon.trigger {
+            Some(ExportTrigger::Charge) if weapon.fire_rate > 0. => 1. / weapon.fire_rate,
+            _ => 0.,
+        },
+        multishot: weapon.multishot.unwrap_or(1.),
+        magazine: weapon.magazine_size.unwrap_or(0),
+        reload: weapon.reload_time.unwrap_or(0.),
+        // The export does not list reserve ammo.
+        ammo: None,
+    };
+
+    let mut attacks = vec![];
+    // Heavy attacks keep the damage distribution of normal attacks.
+    if let Some(heavy_damage) = weapon.heavy_attack_damage
+        && weapon.total_damage > 0.
+    {
+        let scale = heavy_damage / weapon.total_damage;
+        attacks.push(Attack {
+            name: "Heavy Attack".to_owned(),
+            base_damage: normal
+                .base_damage
+                .iter()
+                .map(|(damage_type, damage)| (*damage_type, damage * scale))
+                .collect(),
+            charge_time: weapon.wind_up.unwrap_or(0.),
+            ..normal.clone()
+        });
+    }
+    attacks.insert(0, normal);
+
+    Some(Weapon {
+        name: weapon.name.clone(),
+        mastery_rank: weapon.mastery_req,
+        ty,
+        attacks,
+        progenitor: None,
+    })
+}
+
+/// Damage type of an index of `damagePerShot`, or `None` for the types that
+/// don't deal damage to enemies.
+fn export_damage_type(index: usize) -> Option<DamageType> {
+    use DamageType::*;
+    use Element::*;
+    use PrimaryElement::*;
+    use SecondaryElement::*;
+
+    Some(match index {
+        0 => Physical(Ips::Impact),
+        1 => Physical(Ips::Puncture),
+        2 => Physical(Ips::Slash),
+        3 => Elemental(Primary(Heat)),
+        4 => Elemental(Primary(Cold)),
+        5 => Elemental(Primary(Electricity)),
+        6 => Elemental(Primary(Toxin)),
+        7 => Elemental(Secondary(Blast)),
+        8 => Elemental(Secondary(Radiation)),
+        9 => Elemental(Secondary(Gas)),
+        10 => Elemental(Secondary(Magnetic)),
+        11 => Elemental(Secondary(Viral)),
+        12 => Elemental(Secondary(Corrosive)),
+        13 => Special(crate::damage::Special::Void),
+        14 => Special(crate::damage::Special::Tau),
+        19 => Special(crate::damage::Special::True),
+        _ => return None,
+    })
+}
+
+/// The weapon class comes from the export's slot, or its product category for
+/// weapons without a Tenno slot. The export has no subclass, so it is guessed
+/// from the folders of the unique name, falling back to the most common one.
+fn weapon_type(weapon: &export::Weapon) -> Option<WeaponType> {
+    let name = weapon
+        .unique_name
+        .rsplit_once('/')
+        .map(|(folders, _)| folders)
+        .unwrap_or_default()
+        .to_lowercase();
+    let primary = || WeaponType::Primary(primary_type(&name));
+    let secondary = || WeaponType::Secondary(secondary_type(&name));
+    let melee = || WeaponType::Melee(melee_type(&name));
+
+    Some(match (weapon.product_category, weapon.slot) {
+        // Exalted weapons of Warframe abilities.
+        (ProductCategory::SpecialItems, Some(0)) => WeaponType::Exalted(Box::new(secondary())),
+        (ProductCategory::SpecialItems, Some(1)) => WeaponType::Exalted(Box::new(primary())),
+        (ProductCategory::SpecialItems, Some(5)) => WeaponType::Exalted(Box::new(melee())),
+        (
+            ProductCategory::LongGuns | ProductCategory::Pistols | ProductCategory::Melee,
+            Some(0),
+        ) => secondary(),
+        (
+            ProductCategory::LongGuns | ProductCategory::Pistols | ProductCategory::Melee,
+            Some(1),
+        ) => primary(),
+        (
+            ProductCategory::LongGuns | ProductCategory::Pistols | ProductCategory::Melee,
+            Some(5),
+        ) => melee(),
+        (ProductCategory::LongGuns, _) => primary(),
+        (ProductCategory::Pistols, _) => secondary(),
+        (ProductCategory::Melee, _) => melee(),
+        (ProductCategory::SpaceGuns, _) => WeaponType::Archwing(keyword(
+            &name,
+            &[
+                ("launcher", ArchwingWeaponType::Launcher),
+                ("shotgun", ArchwingWeaponType::Shotgun),
+                ("pistol", ArchwingWeaponType::DualPistols),
+            ],
+            ArchwingWeaponType::Archgun,
+        )),
+        (ProductCategory::SpaceMelee, _) => WeaponType::Archwing(ArchwingWeaponType::Melee),
+        (ProductCategory::SentinelWeapons, _) => WeaponType::Companion(keyword(
+            &name,
+            &[
+                ("glaive", CompanionWeaponType::Glaive),
+                ("sniper", CompanionWeaponType::SniperRifle),
+                ("shotgun", CompanionWeaponType::Shotgun),
+                ("pistol", CompanionWeaponType::Pistol),
+                ("melee", CompanionWeaponType::Melee),
+            ],
+            CompanionWeaponType::Rifle,
+        )),
+        (ProductCategory::OperatorAmps, _) => WeaponType::Modular(ModularWeaponType::Amp),
+        (ProductCategory::CrewShipWeapons, _) => WeaponType::Railjack(keyword(
+            &name,
+            &[("ordnance", RailjackWeaponType::Ordnance)],
+            RailjackWeaponType::Turret,
+        )),
+        _ => return None,
+    })
+}
+
+fn primary_type(name: &str) -> PrimaryWeaponType {
+    use PrimaryWeaponType::*;
+
+    keyword(
+        name,
+        &[
+            ("crossbow", Crossbow),
+            ("bow", Bow),
+            ("speargun", Speargun),
+            ("sniper", SniperRifle),
+            ("shotgun", Shotgun),
+            ("launcher", Launcher),
+            ("armcannon", ArmCannon),
+        ],
+        Rifle,
+    )
+}
+
+fn secondary_type(name: &str) -> SecondaryWeaponType {
+    use SecondaryWeaponType::*;
+
+    keyword(
+        name,
+        &[
+            ("crossbow", Crossbow),
+            ("dualshotgun", DualShotguns),
+            ("shotgun", ShotgunSidearm),
+            ("throw", Thrown),
+            ("tome", Tome),
+            ("dual", DualPistols),
+            ("akimbo", DualPistols),
+        ],
+        Pistol,
+    )
+}
+
+fn melee_type(name: &str) -> MeleeWeaponType {
+    use MeleeWeaponType::*;
+
+    // Longer keywords come first so `heavyscythe` is not taken for `scythe`.
+    keyword(
+        name,
+        &[
+            ("assaultsaw", AssaultSaw),
+            ("bladewhip", BladeAndWhip),
+            ("claw", Claws),
+            ("dualdagger", DualDaggers),
+            ("dagger", Dagger),
+            ("dualnikana", DualNikanas),
+            ("twohandednikana", TwoHandedNikana),
+            ("nikana", Nikana),
+            ("dualsword", DualSwords),
+            ("swordshield", SwordAndShield),
+            ("swordandshield", SwordAndShield),
+            ("fist", Fist),
+            ("glaive", Glaive),
+            ("gunblade", Gunblade),
+            ("hammer", Hammer),
+            ("heavyblade", HeavyBlade),
+            ("heavyscythe", HeavyScythe),
+            ("scythe", Scythe),
+            ("machete", Machete),
+            ("nunchaku", Nunchaku),
+            ("polearm", Polearm),
+            ("rapier", Rapier),
+            ("sparring", Sparring),
+            ("staff", Staff),
+            ("tonfa", Tonfa),
+            ("warfan", Warfan),
+            ("whip", Whip),
+        ],
+        Sword,
+    )
+}
+
+fn keyword<T: Clone>(name: &str, keywords: &[(&str, T)], default: T) -> T {
+    keywords
+        .iter()
+        .find(|(keyword, _)| name.contains(keyword))
+        .map(|(_, value)| value.clone())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::public_export::{upgrade::*, weapon::ExportWeapons};
+
+    use assert_float_eq::assert_f32_near;
+
+    #[test]
+    fn level_stats() {
+        assert_eq!(
+            parse_level_stat("+90% <DT_FREEZE_COLOR>Cold"),
+            Some(ModEffect::Elemental(
+                Element::Primary(PrimaryElement::Cold),
+                0.9
+            ))
+        );
+        assert_eq!(
+            parse_level_stat("+30% Damage to Grineer"),
+            Some(ModEffect::Bane(Faction::Grineer, 0.3))
+        );
+        assert_eq!(
+            parse_level_stat("-15% Ability Strength"),
+            Some(ModEffect::AbilityStrength(-0.15))
+        );
+        assert_eq!(parse_level_stat("Bullets ricochet off walls"), None);
+    }
+
+    #[test]
+    fn upgrade_conversion() {
+        let malignant_force = Upgrade {
+            available_challenges: vec![],
+            base_drain: 4,
+            codex_secret: false,
+            compat_name: "Rifle".to_owned(),
+            description: Description::String("".to_owned()),
+            exclude_from_codex: false,
+            export_avionics: None,
+            name: "Malignant Force".to_owned(),
+            polarity: Polarity::Madurai,
+            rarity: Rarity::Uncommon,
+            fusion_limit: 1,
+            r#type: Type::Primary,
+            is_utility: false,
+            level_stats: vec![
+                LevelStat {
+                    stats: vec!["+30% <DT_POISON>Toxin\r\n+30% Status Chance".to_owned()],
+                },
+                LevelStat {
+                    stats: vec![
+                        "+60% <DT_POISON>Toxin".to_owned(),
+                        "+60% Status Chance".to_owned(),
+                        "Some unknown effect".to_owned(),
+                    ],
+                },
+            ],
+        };
+
+        let conversion = convert(&malignant_force);
+        assert_eq!(conversion.r#mod.ranks.len(), 2);
+        assert_eq!(
+            conversion.r#mod.ranks[0],
+            vec![
+                ModEffect::Elemental(Element::Primary(PrimaryElement::Toxin), 0.3),
+                ModEffect::StatusChance(0.3),
+            ]
+        );
+        assert_eq!(
+            conversion.r#mod.stats,
+            vec![
+                ModEffect::Elemental(Element::Primary(PrimaryElement::Toxin), 0.6).into(),
+                ModEffect::StatusChance(0.6).into(),
+            ]
+        );
+        assert_eq!(conversion.r#mod.max_rank, 1);
+        assert_eq!(
+            conversion.r#mod.stats_at(0),
+            conversion.r#mod.ranks[0]
+                .iter()
+                .cloned()
+                .map(ModStat::from)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(conversion.unparsed, vec!["Some unknown effect".to_owned()]);
+    }
+
+    #[test]
+    fn weapon_conversion() {
+        let json = std::fs::read_to_string("data/public_export/ExportWeapons_en.json")
+            .expect("Should read sample");
+        let export =
+            serde_json::from_str::<ExportWeapons>(&json).expect("Should deserialize sample");
+        let weapons = WeaponLibrary::from_export(&export.export_weapons);
+
+        let hek = weapons.get("Hek").expect("Hek should exist");
+        assert_eq!(hek.ty, WeaponType::Primary(PrimaryWeaponType::Shotgun));
+        assert_eq!(hek.attacks[0].multishot, 7.);
+        assert_eq!(hek.attacks[0].magazine, 4);
+
+        // Its unique name is in a Pistols folder, but the slot makes it a primary.
+        let nagantaka_prime = weapons
+            .get("/Lotus/Weapons/Tenno/Pistols/PrimeNagantaka/PrimeNagantaka")
+            .expect("Nagantaka Prime should exist by unique name");
+        assert_eq!(nagantaka_prime.name, "Nagantaka Prime");
+        assert!(matches!(nagantaka_prime.ty, WeaponType::Primary(_)));
+        assert_eq!(nagantaka_prime.attacks[0].charge_time, 0.);
+        assert_eq!(nagantaka_prime.attacks[0].ammo, None);
+
+        // Charged weapons list the inverse of their charge time as fire rate.
+        let mut json =
+            serde_json::from_str::<serde_json::Value>(&json).expect("Should deserialize sample");
+        json["ExportWeapons"][1]["trigger"] = "CHARGE".into();
+        let export =
+            serde_json::from_value::<ExportWeapons>(json).expect("Should deserialize sample");
+        let charged =
+            convert_weapon(&export.export_weapons[1]).expect("Nagantaka Prime should convert");
+        assert_f32_near!(charged.attacks[0].charge_time, 1. / 2.33);
+
+        let kuva_nukor = weapons.get("Kuva Nukor").expect("Kuva Nukor should exist");
+        assert_eq!(
+            kuva_nukor.attacks[0].base_damage,
+            HashMap::from([(
+                DamageType::Elemental(Element::Secondary(SecondaryElement::Radiation)),
+                21.
+            )])
+        );
+
+        let guandao_prime = weapons
+            .get("Guandao Prime")
+            .expect("Guandao Prime should exist");
+        assert_eq!(
+            guandao_prime.ty,
+            WeaponType::Melee(MeleeWeaponType::Polearm)
+        );
+        let heavy = guandao_prime
+            .attack("Heavy Attack")
+            .expect("Melee weapons should have a heavy attack");
+        assert_f32_near!(heavy.base_damage.values().sum::<f32>(), 1750.);
+    }
+}
@@ -1 +1,9 @@
+pub mod cache;
+pub mod convert;
+pub mod recipe;
+pub mod relic_arcane;
+pub mod resource;
+pub mod sentinel;
 pub mod upgrade;
+pub mod warframe;
+pub mod weapon;
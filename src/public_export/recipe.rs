@@ -0,0 +1,56 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct ExportRecipes {
+    pub export_recipes: Vec<Recipe>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct Recipe {
+    pub unique_name: String,
+    pub result_type: String,
+    /// Credits to start building.
+    pub build_price: u32,
+    /// Build time in seconds.
+    pub build_time: u32,
+    /// Platinum to rush the build.
+    pub skip_build_time_price: u32,
+    pub consume_on_use: bool,
+    /// Items built at once.
+    pub num: u32,
+    pub codex_secret: bool,
+    #[serde(default)]
+    pub exclude_from_codex: bool,
+    pub prime_selling_price: Option<u32>,
+    pub ingredients: Vec<Ingredient>,
+    #[serde(default)]
+    pub secret_ingredients: Vec<Ingredient>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct Ingredient {
+    pub item_type: String,
+    pub item_count: u32,
+    pub product_category: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sample() {
+        let json = std::fs::read_to_string("data/public_export/ExportRecipes_en.json")
+            .expect("Should read sample");
+        let export =
+            serde_json::from_str::<ExportRecipes>(&json).expect("Should deserialize sample");
+
+        let braton_prime = &export.export_recipes[0];
+        assert_eq!(braton_prime.ingredients.len(), 4);
+        assert_eq!(braton_prime.ingredients[0].item_count, 1);
+        assert_eq!(braton_prime.build_time, 43200);
+    }
+}
@@ -0,0 +1,66 @@
+use crate::public_export::upgrade::{LevelStat, Rarity};
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct ExportRelicArcane {
+    pub export_relic_arcane: Vec<RelicArcane>,
+}
+
+/// A Void Relic or an Arcane, which the export lists together. Relics have
+/// rewards, and Arcanes have a rarity and stats per rank.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct RelicArcane {
+    pub unique_name: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub codex_secret: bool,
+    #[serde(default)]
+    pub exclude_from_codex: bool,
+    pub rarity: Option<Rarity>,
+    #[serde(default)]
+    pub level_stats: Vec<LevelStat>,
+    #[serde(default)]
+    pub relic_rewards: Vec<RelicReward>,
+}
+
+impl RelicArcane {
+    pub fn is_relic(&self) -> bool {
+        !self.relic_rewards.is_empty()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct RelicReward {
+    pub reward_name: String,
+    pub rarity: Rarity,
+    pub tier: u32,
+    pub item_count: u32,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sample() {
+        let json = std::fs::read_to_string("data/public_export/ExportRelicArcane_en.json")
+            .expect("Should read sample");
+        let export =
+            serde_json::from_str::<ExportRelicArcane>(&json).expect("Should deserialize sample");
+
+        let (relics, arcanes): (Vec<_>, Vec<_>) = export
+            .export_relic_arcane
+            .iter()
+            .partition(|item| item.is_relic());
+        assert_eq!(relics.len(), 1);
+        assert_eq!(relics[0].relic_rewards.len(), 6);
+        assert_eq!(arcanes.len(), 2);
+        assert_eq!(arcanes[0].level_stats.len(), 6);
+        assert!(matches!(arcanes[0].rarity, Some(Rarity::Legendary)));
+    }
+}
@@ -0,0 +1,40 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct ExportResources {
+    pub export_resources: Vec<Resource>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct Resource {
+    pub unique_name: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub codex_secret: bool,
+    #[serde(default)]
+    pub exclude_from_codex: bool,
+    pub parent_name: String,
+    #[serde(default)]
+    pub show_in_inventory: bool,
+    /// Ducats a Prime part sells for.
+    pub prime_selling_price: Option<u32>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sample() {
+        let json = std::fs::read_to_string("data/public_export/ExportResources_en.json")
+            .expect("Should read sample");
+        let export =
+            serde_json::from_str::<ExportResources>(&json).expect("Should deserialize sample");
+
+        assert_eq!(export.export_resources.len(), 2);
+        assert_eq!(export.export_resources[1].prime_selling_price, Some(45));
+    }
+}
@@ -0,0 +1,54 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct ExportSentinels {
+    pub export_sentinels: Vec<Sentinel>,
+}
+
+/// A companion, such as a sentinel, a Kubrow or a Kavat.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct Sentinel {
+    pub unique_name: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub health: f32,
+    pub shield: f32,
+    pub armor: f32,
+    pub stamina: f32,
+    pub power: f32,
+    pub codex_secret: bool,
+    #[serde(default)]
+    pub exclude_from_codex: bool,
+    pub product_category: ProductCategory,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub enum ProductCategory {
+    Sentinels,
+    KubrowPets,
+    SpecialItems,
+    #[serde(other)]
+    Error,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sample() {
+        let json = std::fs::read_to_string("data/public_export/ExportSentinels_en.json")
+            .expect("Should read sample");
+        let export =
+            serde_json::from_str::<ExportSentinels>(&json).expect("Should deserialize sample");
+
+        assert_eq!(export.export_sentinels.len(), 2);
+        assert_eq!(
+            export.export_sentinels[1].product_category,
+            ProductCategory::KubrowPets
+        );
+    }
+}
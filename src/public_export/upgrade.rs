@@ -1,5 +1,11 @@
 use serde::Deserialize;
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct ExportUpgrades {
+    pub export_upgrades: Vec<Upgrade>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all(deserialize = "camelCase"))]
 pub struct Upgrade {
@@ -16,6 +22,9 @@ pub struct Upgrade {
     pub rarity: Rarity,
     pub fusion_limit: u32,
     pub r#type: Type,
+    /// Whether the mod fits the exilus slot.
+    #[serde(default)]
+    pub is_utility: bool,
     pub level_stats: Vec<LevelStat>,
 }
 
@@ -25,18 +34,18 @@ pub enum Polarity {
     Madurai,
     #[serde(rename(deserialize = "AP_DEFENSE"))]
     Vazarin,
-    #[serde(rename(deserialize = "AP_POWER"))]
+    #[serde(rename(deserialize = "AP_TACTIC"))]
     Naramon,
+    #[serde(rename(deserialize = "AP_POWER"))]
+    Zenurik,
+    #[serde(rename(deserialize = "AP_WARD"))]
+    Unairu,
     #[serde(rename(deserialize = "AP_PRECEPT"))]
-    Precept,
-    #[serde(rename(deserialize = "AP_TACTIC"))]
-    Tactic,
+    Penjaga,
     #[serde(rename(deserialize = "AP_UMBRA"))]
     Umbra,
     #[serde(rename(deserialize = "AP_UNIVERSAL"), alias = "AP_ANY")]
     Universal,
-    #[serde(rename(deserialize = "AP_WARD"))]
-    Aura,
     #[serde(other)]
     Error,
 }
@@ -0,0 +1,82 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct ExportWarframes {
+    pub export_warframes: Vec<Warframe>,
+    #[serde(default)]
+    pub export_abilities: Vec<ExportAbility>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct Warframe {
+    pub unique_name: String,
+    pub name: String,
+    pub parent_name: String,
+    #[serde(default)]
+    pub description: String,
+    pub health: f32,
+    pub shield: f32,
+    pub armor: f32,
+    pub stamina: f32,
+    pub power: f32,
+    pub codex_secret: bool,
+    #[serde(default)]
+    pub mastery_req: u32,
+    pub sprint_speed: f32,
+    pub passive_description: Option<String>,
+    /// Unique names of the exalted weapons granted by abilities.
+    #[serde(default)]
+    pub exalted: Vec<String>,
+    #[serde(default)]
+    pub abilities: Vec<Ability>,
+    pub product_category: ProductCategory,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct Ability {
+    pub ability_unique_name: String,
+    pub ability_name: String,
+    pub description: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct ExportAbility {
+    pub unique_name: String,
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub enum ProductCategory {
+    Suits,
+    SpaceSuits,
+    MechSuits,
+    #[serde(other)]
+    Error,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sample() {
+        let json = std::fs::read_to_string("data/public_export/ExportWarframes_en.json")
+            .expect("Should read sample");
+        let export =
+            serde_json::from_str::<ExportWarframes>(&json).expect("Should deserialize sample");
+
+        let excalibur = &export.export_warframes[0];
+        assert_eq!(excalibur.product_category, ProductCategory::Suits);
+        assert_eq!(excalibur.abilities.len(), 4);
+        assert_eq!(excalibur.exalted.len(), 1);
+        assert_eq!(
+            export.export_warframes[1].product_category,
+            ProductCategory::SpaceSuits
+        );
+    }
+}
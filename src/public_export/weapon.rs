@@ -0,0 +1,108 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "PascalCase"))]
+pub struct ExportWeapons {
+    pub export_weapons: Vec<Weapon>,
+    #[serde(default)]
+    pub export_railjack_weapons: Vec<Weapon>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct Weapon {
+    pub unique_name: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub codex_secret: bool,
+    #[serde(default)]
+    pub exclude_from_codex: bool,
+    pub product_category: ProductCategory,
+    pub slot: Option<u32>,
+    #[serde(default)]
+    pub mastery_req: u32,
+    /// Damage of each type, indexed like `DamageType`'s export order.
+    pub damage_per_shot: Vec<f32>,
+    pub total_damage: f32,
+    pub critical_chance: f32,
+    pub critical_multiplier: f32,
+    pub proc_chance: f32,
+    pub fire_rate: f32,
+    pub multishot: Option<f32>,
+    pub magazine_size: Option<u32>,
+    pub reload_time: Option<f32>,
+    pub trigger: Option<Trigger>,
+    pub noise: Option<Noise>,
+    pub accuracy: Option<f32>,
+    pub omega_attenuation: Option<f32>,
+
+    // Melee
+    pub range: Option<f32>,
+    pub blocking_angle: Option<f32>,
+    pub combo_duration: Option<f32>,
+    pub follow_through: Option<f32>,
+    pub slam_attack: Option<f32>,
+    pub heavy_attack_damage: Option<f32>,
+    pub wind_up: Option<f32>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub enum ProductCategory {
+    LongGuns,
+    Pistols,
+    Melee,
+    SpaceGuns,
+    SpaceMelee,
+    SentinelWeapons,
+    SpecialItems,
+    OperatorAmps,
+    CrewShipWeapons,
+    #[serde(other)]
+    Error,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Trigger {
+    Auto,
+    Semi,
+    Burst,
+    Held,
+    Charge,
+    Duplex,
+    Active,
+    #[serde(rename(deserialize = "AUTO BURST"))]
+    AutoBurst,
+    #[serde(other)]
+    Error,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Noise {
+    Alarming,
+    Silent,
+    #[serde(other)]
+    Error,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sample() {
+        let json = std::fs::read_to_string("data/public_export/ExportWeapons_en.json")
+            .expect("Should read sample");
+        let export =
+            serde_json::from_str::<ExportWeapons>(&json).expect("Should deserialize sample");
+
+        let braton_prime = &export.export_weapons[0];
+        assert_eq!(braton_prime.product_category, ProductCategory::LongGuns);
+        assert_eq!(braton_prime.trigger, Some(Trigger::Auto));
+        assert_eq!(braton_prime.damage_per_shot.len(), 20);
+        assert!(export.export_weapons.iter().any(|w| w.range.is_some()));
+        assert_eq!(export.export_railjack_weapons.len(), 1);
+    }
+}
@@ -0,0 +1,381 @@
+use crate::{
+    mods::{Compatibility, Mod, ModEffect, ModStat, Polarity, Rarity},
+    weapon::{ArchwingWeaponType, ModularWeaponType, PrimaryWeaponType, WeaponType},
+};
+
+use std::{collections::HashMap, path::Path};
+
+use rand::{Rng, seq::SliceRandom};
+use serde::{Deserialize, Serialize};
+
+pub const MAX_RANK: u32 = 8;
+/// Capacity drain of a riven at rank 0.
+const BASE_DRAIN: u32 = 10;
+/// Every roll falls within 10% of the average value of its stat.
+const ROLL_SPREAD: f32 = 0.1;
+
+/// Weapon class a riven is rolled for, which decides the stats it can roll.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum RivenClass {
+    Rifle,
+    Shotgun,
+    Pistol,
+    Melee,
+    Archgun,
+}
+
+impl RivenClass {
+    /// Class of the rivens of a weapon type, or `None` if it has no rivens.
+    pub fn of(ty: &WeaponType) -> Option<Self> {
+        Some(match ty {
+            WeaponType::Primary(PrimaryWeaponType::Shotgun) => RivenClass::Shotgun,
+            WeaponType::Primary(_) => RivenClass::Rifle,
+            WeaponType::Secondary(_) => RivenClass::Pistol,
+            WeaponType::Melee(_) => RivenClass::Melee,
+            WeaponType::Modular(ModularWeaponType::Shotgun) => RivenClass::Shotgun,
+            WeaponType::Modular(ModularWeaponType::Rifle | ModularWeaponType::Launcher) => {
+                RivenClass::Rifle
+            }
+            WeaponType::Modular(ModularWeaponType::Pistol) => RivenClass::Pistol,
+            WeaponType::Modular(ModularWeaponType::Melee(_)) => RivenClass::Melee,
+            WeaponType::Archwing(ArchwingWeaponType::Melee) => return None,
+            WeaponType::Archwing(_) => RivenClass::Archgun,
+            _ => return None,
+        })
+    }
+}
+
+/// Disposition tier shown as 1 to 5 dots, from a weapon's riven disposition.
+pub fn disposition_tier(disposition: f32) -> u32 {
+    match disposition {
+        d if d < 0.7 => 1,
+        d if d < 0.9 => 2,
+        d if d <= 1.1 => 3,
+        d if d <= 1.3 => 4,
+        _ => 5,
+    }
+}
+
+/// Lowest and highest disposition of a tier.
+pub fn tier_dispositions(tier: u32) -> Option<(f32, f32)> {
+    Some(match tier {
+        1 => (0.5, 0.69),
+        2 => (0.7, 0.89),
+        3 => (0.9, 1.1),
+        4 => (1.11, 1.3),
+        5 => (1.31, 1.55),
+        _ => return None,
+    })
+}
+
+/// Number of positive stats and whether there is a negative one. Rivens with
+/// more stats roll lower values on each.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Layout {
+    pub positives: usize,
+    pub negative: bool,
+}
+
+impl Layout {
+    /// Multipliers of the positive and negative stats.
+    pub fn multipliers(&self) -> (f32, f32) {
+        match (self.positives, self.negative) {
+            (..=2, false) => (0.99, 0.),
+            (..=2, true) => (1.2375, 0.495),
+            (_, false) => (0.75, 0.),
+            (_, true) => (0.9375, 0.75),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct Riven {
+    pub name: String,
+    /// Rivens apply to every variant of their weapon, e.g. `Braton` for both
+    /// Braton and Braton Prime.
+    pub weapon: String,
+    pub class: RivenClass,
+    /// Riven disposition of the weapon, from 0.5 to 1.55.
+    pub disposition: f32,
+    pub polarity: Polarity,
+    /// Stats at max rank.
+    pub positives: Vec<ModEffect>,
+    pub negative: Option<ModEffect>,
+    /// Current rank, `None` meaning fully ranked.
+    #[serde(default)]
+    pub rank: Option<u32>,
+}
+
+impl Riven {
+    pub fn layout(&self) -> Layout {
+        Layout {
+            positives: self.positives.len(),
+            negative: self.negative.is_some(),
+        }
+    }
+
+    pub fn to_mod(&self) -> Mod {
+        Mod {
+            name: self.name.clone(),
+            stats: self
+                .positives
+                .iter()
+                .chain(self.negative.iter())
+                .cloned()
+                .map(ModStat::from)
+                .collect(),
+            ranks: vec![],
+            max_rank: MAX_RANK,
+            rank: self.rank,
+            rarity: Rarity::Rare,
+            base_drain: BASE_DRAIN,
+            polarity: Some(self.polarity),
+            // Only one riven can be equipped on a weapon.
+            family: Some("Riven".to_owned()),
+            compatibility: Compatibility::Weapon(self.weapon.clone()),
+            exilus: false,
+        }
+    }
+}
+
+/// Where a riven's stat falls within the range it can roll, at max rank.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Grade {
+    pub effect: ModEffect,
+    /// Lowest and highest magnitude the stat can roll.
+    pub min: f32,
+    pub max: f32,
+    /// 0 for the lowest magnitude and 1 for the highest. Negative stats are
+    /// best at 0.
+    pub position: f32,
+}
+
+/// Average value of every stat a riven can roll for each class, at
+/// disposition 1 and before the layout multipliers.
+pub struct RivenTable {
+    stats: HashMap<RivenClass, Vec<ModEffect>>,
+}
+
+impl RivenTable {
+    pub fn load(path: &Path) -> Self {
+        Self::try_load(path).expect("Should load riven table")
+    }
+
+    /// Like `load`, but returns an error instead of panicking if the file
+    /// cannot be read or deserialized.
+    pub fn try_load(path: &Path) -> Result<Self, String> {
+        let file_content = std::fs::read_to_string(path)
+            .map_err(|error| format!("Cannot read {}: {error}", path.display()))?;
+        let stats = ron::from_str::<HashMap<RivenClass, Vec<ModEffect>>>(file_content.as_str())
+            .map_err(|error| format!("Cannot deserialize {}: {error}", path.display()))?;
+        Ok(Self { stats })
+    }
+
+    pub fn stats(&self, class: RivenClass) -> &[ModEffect] {
+        self.stats.get(&class).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    fn base(&self, class: RivenClass, effect: &ModEffect) -> Option<f32> {
+        self.stats(class)
+            .iter()
+            .find(|stat| same_stat(stat, effect))
+            .map(|stat| stat.clone().value_mut().abs())
+    }
+
+    /// Lowest and highest magnitude a stat can roll at max rank.
+    pub fn range(
+        &self,
+        class: RivenClass,
+        effect: &ModEffect,
+        disposition: f32,
+        layout: Layout,
+        negative: bool,
+    ) -> Option<(f32, f32)> {
+        let (positive_multiplier, negative_multiplier) = layout.multipliers();
+        let multiplier = if negative {
+            negative_multiplier
+        } else {
+            positive_multiplier
+        };
+        let average = self.base(class, effect)? * disposition * multiplier;
+        Some((average * (1. - ROLL_SPREAD), average * (1. + ROLL_SPREAD)))
+    }
+
+    /// Lowest and highest magnitude a stat can roll at max rank, on any
+    /// weapon of a disposition tier.
+    pub fn tier_range(
+        &self,
+        class: RivenClass,
+        effect: &ModEffect,
+        tier: u32,
+        layout: Layout,
+        negative: bool,
+    ) -> Option<(f32, f32)> {
+        let (lowest, highest) = tier_dispositions(tier)?;
+        let (min, _) = self.range(class, effect, lowest, layout, negative)?;
+        let (_, max) = self.range(class, effect, highest, layout, negative)?;
+        Some((min, max))
+    }
+
+    /// Grades every stat of a riven, or `None` if it has a stat its class
+    /// cannot roll.
+    pub fn grade(&self, riven: &Riven) -> Option<Vec<Grade>> {
+        let layout = riven.layout();
+        riven
+            .positives
+            .iter()
+            .map(|effect| (effect, false))
+            .chain(riven.negative.iter().map(|effect| (effect, true)))
+            .map(|(effect, negative)| {
+                let (min, max) =
+                    self.range(riven.class, effect, riven.disposition, layout, negative)?;
+                let value = effect.clone().value_mut().abs();
+                Some(Grade {
+                    effect: effect.clone(),
+                    min,
+                    max,
+                    position: ((value - min) / (max - min)).clamp(0., 1.),
+                })
+            })
+            .collect()
+    }
+
+    /// Rolls a random max rank riven, as unveiling one would.
+    pub fn sample(
+        &self,
+        class: RivenClass,
+        weapon: &str,
+        disposition: f32,
+        rng: &mut impl Rng,
+    ) -> Riven {
+        let layout = Layout {
+            positives: if rng.gen_bool(0.5) { 2 } else { 3 },
+            negative: rng.gen_bool(0.5),
+        };
+        let (positive_multiplier, negative_multiplier) = layout.multipliers();
+
+        let count = layout.positives + layout.negative as usize;
+        let mut stats = self
+            .stats(class)
+            .choose_multiple(rng, count)
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut roll = |mut effect: ModEffect, multiplier: f32| {
+            let spread = rng.gen_range(1. - ROLL_SPREAD..=1. + ROLL_SPREAD);
+            *effect.value_mut() *= disposition * multiplier * spread;
+            effect
+        };
+
+        let negative = if layout.negative && stats.len() == count {
+            stats.pop().map(|effect| roll(effect, -negative_multiplier))
+        } else {
+            None
+        };
+        let positives = stats
+            .into_iter()
+            .map(|effect| roll(effect, positive_multiplier))
+            .collect();
+
+        let polarity = *[Polarity::Madurai, Polarity::Vazarin, Polarity::Naramon]
+            .choose(rng)
+            .expect("Polarities should not be empty");
+
+        Riven {
+            name: format!("{weapon} Riven"),
+            weapon: weapon.to_owned(),
+            class,
+            disposition,
+            polarity,
+            positives,
+            negative,
+            rank: None,
+        }
+    }
+}
+
+/// Whether two effects are the same stat, regardless of their values.
+fn same_stat(l: &ModEffect, r: &ModEffect) -> bool {
+    let (mut l, mut r) = (l.clone(), r.clone());
+    *l.value_mut() = 0.;
+    *r.value_mut() = 0.;
+    l == r
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{build::Loadout, build::SlotId, weapon::WeaponLibrary};
+
+    use assert_float_eq::assert_f32_near;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    #[test]
+    fn grading() {
+        let table = RivenTable::load(Path::new("data/rivens.ron"));
+        let riven = Riven {
+            name: "Braton Critacan".to_owned(),
+            weapon: "Braton".to_owned(),
+            class: RivenClass::Rifle,
+            disposition: 1.2,
+            polarity: Polarity::Madurai,
+            // Average critical chance and the highest possible critical damage.
+            positives: vec![
+                ModEffect::CriticalChance(1.5 * 1.2 * 1.2375),
+                ModEffect::CriticalDamage(1.2 * 1.2 * 1.2375 * 1.1),
+            ],
+            negative: Some(ModEffect::ReloadSpeed(-0.5 * 1.2 * 0.495 * 0.9)),
+            rank: None,
+        };
+
+        let grades = table.grade(&riven).expect("Every stat should be rollable");
+        assert_f32_near!(grades[0].position, 0.5, 16);
+        assert_f32_near!(grades[1].position, 1.);
+        assert_f32_near!(grades[2].position, 0.);
+
+        let weapons = WeaponLibrary::load(Path::new("data/weapons.ron"));
+        let mut loadout = Loadout::new(
+            weapons
+                .get("Braton Prime")
+                .expect("Braton Prime should exist")
+                .clone(),
+        );
+        loadout
+            .equip(SlotId::Mod(0), riven.to_mod())
+            .expect("Braton rivens should fit Braton Prime");
+        assert_eq!(loadout.drain(), BASE_DRAIN + MAX_RANK);
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(
+            RivenClass::of(&WeaponType::Archwing(ArchwingWeaponType::Rifle)),
+            Some(RivenClass::Archgun)
+        );
+        assert_eq!(
+            RivenClass::of(&WeaponType::Archwing(ArchwingWeaponType::Melee)),
+            None
+        );
+
+        let table = RivenTable::load(Path::new("data/rivens.ron"));
+        assert!(!table.stats(RivenClass::Archgun).is_empty());
+    }
+
+    #[test]
+    fn sampling() {
+        let table = RivenTable::load(Path::new("data/rivens.ron"));
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..100 {
+            let riven = table.sample(RivenClass::Shotgun, "Hek", 0.85, &mut rng);
+            assert!((2..=3).contains(&riven.positives.len()));
+            let grades = table
+                .grade(&riven)
+                .expect("Sampled stats should be rollable");
+            assert!(
+                grades
+                    .iter()
+                    .all(|grade| (0. ..=1.).contains(&grade.position))
+            );
+        }
+    }
+}
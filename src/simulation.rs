@@ -0,0 +1,377 @@
+use crate::{
+    damage::{DamageType, Special},
+    enemy::{Enemy, Pool},
+    hit::Hit,
+    status::{self, StatusEngine},
+};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+/// Outcome of a single simulated kill.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Kill {
+    /// Seconds between the first shot and the killing one.
+    pub time: f32,
+    pub shots: u32,
+}
+
+/// Time to kill distribution over many simulated kills.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TimeToKill {
+    pub runs: usize,
+    /// Runs where the target died before the weapon ran out of ammo.
+    pub kills: usize,
+    pub mean: f32,
+    pub median: f32,
+    pub p95: f32,
+}
+
+/// Remaining pools of the simulated target.
+#[derive(Debug, Clone, Copy)]
+struct Target {
+    overguard: f32,
+    shield: f32,
+    health: f32,
+}
+
+/// Per-pellet damage to each pool before critical hits.
+#[derive(Debug, Clone, Copy)]
+struct PelletDamage {
+    overguard: f32,
+    shield: f32,
+    shield_bypass: f32,
+    health: f32,
+}
+
+impl PelletDamage {
+    fn of(hit: &Hit) -> Self {
+        Self {
+            overguard: hit.damage_to(Pool::Overguard),
+            shield: hit.damage_to(Pool::Shield),
+            shield_bypass: hit.damage_bypassing(Pool::Shield),
+            health: hit.damage_to(Pool::Health),
+        }
+    }
+}
+
+impl Target {
+    fn is_dead(&self) -> bool {
+        self.health <= 0.
+    }
+
+    fn take(&mut self, damage: &PelletDamage, multiplier: f32) {
+        // Fraction of the pellet left once a pool breaks.
+        let mut fraction = 1.;
+
+        if self.overguard > 0. {
+            let dealt = damage.overguard * multiplier;
+            if dealt < self.overguard {
+                self.overguard -= dealt;
+                return;
+            }
+            fraction -= self.overguard / dealt;
+            self.overguard = 0.;
+        }
+
+        if self.shield > 0. {
+            let dealt = damage.shield * multiplier * fraction;
+            self.health -= damage.shield_bypass * multiplier * fraction;
+            if dealt < self.shield {
+                self.shield -= dealt;
+                return;
+            }
+            fraction *= 1. - self.shield / dealt;
+            self.shield = 0.;
+            self.health -= (damage.health - damage.shield_bypass) * multiplier * fraction;
+            return;
+        }
+
+        self.health -= damage.health * multiplier * fraction;
+    }
+
+    fn take_tick(&mut self, enemy: &Enemy, damage_type: &DamageType, damage: f32) {
+        let skips_shield = enemy.bypasses(Pool::Shield, damage_type)
+            || *damage_type == DamageType::Special(Special::True);
+
+        if self.overguard > 0. {
+            self.overguard -= damage * enemy.modifier(Pool::Overguard, damage_type);
+        } else if self.shield > 0. && !skips_shield {
+            self.shield -= damage * enemy.modifier(Pool::Shield, damage_type);
+        } else {
+            self.health -= damage * enemy.modifier(Pool::Health, damage_type);
+        }
+    }
+}
+
+/// Picks the first outcome whose cumulative chance exceeds `roll`.
+fn pick<T: Copy>(outcomes: &[(T, f32)], roll: f32) -> Option<T> {
+    outcomes
+        .iter()
+        .scan(0., |cumulative, (outcome, chance)| {
+            *cumulative += chance;
+            Some((*outcome, *cumulative))
+        })
+        .find(|(_, cumulative)| roll < *cumulative)
+        .map(|(outcome, _)| outcome)
+        .or(outcomes.last().map(|(outcome, _)| *outcome))
+}
+
+/// Number of times an event with an expected count of `expected` happens,
+/// e.g. pellets from fractional multishot or procs above 100% status chance.
+fn roll_count(expected: f32, rng: &mut impl Rng) -> u32 {
+    let expected = expected.max(0.);
+    expected.floor() as u32 + rng.gen_bool(expected.fract() as f64) as u32
+}
+
+/// Fires the hit's attack shot by shot, reloading when the magazine runs dry,
+/// until the enemy dies. Each pellet rolls its crit tier and status procs, and
+/// damage over time ticks and debuffs apply between shots. Returns `None` if
+/// the weapon runs out of ammo or cannot damage the target.
+///
+/// A magazine of 0 means the attack never reloads.
+pub fn simulate(hit: &Hit, rng: &mut impl Rng) -> Option<Kill> {
+    let attack = hit.attack();
+    let (multishot, interval) = (hit.multishot(), hit.shot_interval());
+    let (full_magazine, reload) = (hit.magazine(), hit.reload());
+    let tiers = hit.critical().tier_chances();
+    let status_chance = hit.total_status_chance();
+    let proc_weights = hit
+        .status_chance()
+        .into_iter()
+        .map(|(damage_type, chance)| (damage_type, chance / status_chance))
+        .collect::<Vec<_>>();
+
+    let mut current = hit.clone();
+    let mut damage = PelletDamage::of(&current);
+    if damage.health <= 0. {
+        return None;
+    }
+
+    let enemy = hit.enemy();
+    // Whether procs tick through the shields or through health once they
+    // break, as shield and health modifiers only scale with debuffs.
+    let ticks_through_shield = proc_weights.iter().any(|(damage_type, _)| {
+        let dot_type = status::dot_damage_type(damage_type);
+        let pool = if enemy.bypasses(Pool::Shield, &dot_type)
+            || dot_type == DamageType::Special(Special::True)
+        {
+            Pool::Health
+        } else {
+            Pool::Shield
+        };
+        hit.dot_tick_damage(damage_type)
+            .is_some_and(|tick| tick > 0.)
+            && enemy.modifier(pool, &dot_type) > 0.
+    });
+    if enemy.scaled_shield() > 0.
+        && damage.shield <= 0.
+        && damage.shield_bypass <= 0.
+        && !ticks_through_shield
+    {
+        return None;
+    }
+    let mut target = Target {
+        overguard: enemy.scaled_overguard(),
+        shield: enemy.scaled_shield(),
+        health: enemy.scaled_health(),
+    };
+    let mut statuses = StatusEngine::default();
+
+    let mut time = 0.;
+    let mut shots = 0;
+    let mut magazine = full_magazine;
+    let mut reserve = attack.ammo.unwrap_or(0);
+    let mut out_of_ammo = false;
+
+    loop {
+        while let Some(tick) = statuses.next_tick()
+            && (tick <= time || out_of_ammo)
+        {
+            for (damage_type, tick_damage) in statuses.advance(tick) {
+                target.take_tick(current.enemy(), &damage_type, tick_damage);
+            }
+            if target.is_dead() {
+                return Some(Kill { time: tick, shots });
+            }
+        }
+
+        if out_of_ammo {
+            return None;
+        }
+
+        let critical = current.critical();
+        for _ in 0..roll_count(multishot, rng) {
+            let tier = pick(&tiers, rng.r#gen()).expect("Crit tiers should not be empty");
+            let multiplier = critical.tier_multiplier(tier);
+            target.take(&damage, multiplier);
+
+            for _ in 0..roll_count(status_chance, rng) {
+                let Some(damage_type) = pick(&proc_weights, rng.r#gen()) else {
+                    break;
+                };
+                let tick_damage = current.dot_tick_damage(&damage_type).unwrap_or(0.);
+                statuses.apply(
+                    damage_type,
+                    time,
+                    current.status_duration(&damage_type),
+                    tick_damage * multiplier,
+                );
+            }
+        }
+        shots += 1;
+
+        let debuffs = statuses.debuffs();
+        if debuffs != current.enemy().debuffs {
+            current = current.with_debuffs(debuffs);
+            damage = PelletDamage::of(&current);
+        }
+
+        if target.is_dead() {
+            return Some(Kill { time, shots });
+        }
+
+        if full_magazine == 0 {
+            time += interval;
+            continue;
+        }
+
+        magazine -= 1;
+        if magazine > 0 {
+            time += interval;
+            continue;
+        }
+
+        if attack.ammo.is_none() {
+            magazine = full_magazine;
+        } else if reserve > 0 {
+            magazine = full_magazine.min(reserve);
+            reserve -= magazine;
+        } else {
+            out_of_ammo = true;
+        }
+        time += reload;
+    }
+}
+
+/// Runs `runs` simulations from a seeded RNG and summarizes their time to kill.
+pub fn time_to_kill(hit: &Hit, runs: usize, seed: u64) -> Option<TimeToKill> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut times = (0..runs)
+        .filter_map(|_| simulate(hit, &mut rng))
+        .map(|kill| kill.time)
+        .collect::<Vec<_>>();
+
+    if times.is_empty() {
+        return None;
+    }
+    times.sort_by(f32::total_cmp);
+
+    let percentile = |p: f32| times[((times.len() - 1) as f32 * p).round() as usize];
+
+    Some(TimeToKill {
+        runs,
+        kills: times.len(),
+        mean: times.iter().sum::<f32>() / times.len() as f32,
+        median: percentile(0.5),
+        p95: percentile(0.95),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        damage::{DamageType, Ips},
+        enemy::{Enemy, Faction},
+        weapon::Attack,
+    };
+    use assert_float_eq::assert_f32_near;
+
+    fn attack(critical_chance: f32) -> Attack {
+        Attack {
+            name: "Normal Attack".to_owned(),
+            base_damage: [(DamageType::Physical(Ips::Puncture), 25.)].into(),
+            critical_chance,
+            critical_multiplier: 2.,
+            status_chance: 0.,
+            fire_rate: 2.,
+            charge_time: 0.,
+            multishot: 1.,
+            magazine: 2,
+            reload: 1.,
+            ammo: Some(10),
+        }
+    }
+
+    fn target() -> Enemy {
+        Enemy {
+            health: 100.,
+            ..Enemy::new(Faction::Infested)
+        }
+    }
+
+    #[test]
+    fn deterministic_kill() {
+        let hit = Hit::new(&attack(0.), vec![], target());
+        let kill = simulate(&hit, &mut StdRng::seed_from_u64(0)).expect("Target should die");
+
+        // Two shots, a reload, then two more shots.
+        assert_eq!(kill.shots, 4);
+        assert_f32_near!(kill.time, 2.);
+    }
+
+    #[test]
+    fn seeded_time_to_kill() {
+        let hit = Hit::new(&attack(0.5), vec![], target());
+
+        let ttk = time_to_kill(&hit, 500, 42).expect("Target should die");
+        assert_eq!(ttk, time_to_kill(&hit, 500, 42).unwrap());
+        assert_eq!(ttk.kills, 500);
+        // Between all crits (2 shots) and no crits (4 shots).
+        assert!((0.5..=2.).contains(&ttk.mean));
+        assert!(ttk.median <= ttk.p95);
+    }
+
+    #[test]
+    fn out_of_ammo() {
+        let mut attack = attack(0.);
+        attack.ammo = Some(1);
+
+        let hit = Hit::new(&attack, vec![], target());
+        assert_eq!(simulate(&hit, &mut StdRng::seed_from_u64(0)), None);
+    }
+
+    #[test]
+    fn unbreakable_shields() {
+        let mut attack = attack(0.);
+        attack.ammo = None;
+        let shielded = Enemy {
+            shield: 100.,
+            shield_weaknesses: [(DamageType::Physical(Ips::Puncture), 0.)].into(),
+            ..target()
+        };
+
+        let hit = Hit::new(&attack, vec![], shielded.clone());
+        assert_eq!(simulate(&hit, &mut StdRng::seed_from_u64(0)), None);
+        attack.magazine = 0;
+        let hit = Hit::new(&attack, vec![], shielded);
+        assert_eq!(simulate(&hit, &mut StdRng::seed_from_u64(0)), None);
+    }
+
+    #[test]
+    fn bleed_finishes_kill() {
+        let mut attack = attack(0.);
+        attack.base_damage = [(DamageType::Physical(Ips::Slash), 20.)].into();
+        attack.status_chance = 1.;
+        attack.magazine = 1;
+        attack.ammo = Some(1);
+
+        // Both shots deal 30 damage and bleed for 7 True damage per tick,
+        // finishing the target after the weapon runs dry.
+        let hit = Hit::new(&attack, vec![], target());
+        let kill = simulate(&hit, &mut StdRng::seed_from_u64(0)).expect("Bleed should kill");
+        assert_eq!(kill.shots, 2);
+        assert!(kill.time > 1.);
+    }
+}
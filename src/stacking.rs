@@ -0,0 +1,262 @@
+use crate::mods::{Mod, ReduceAmount, StackingBehaviour, TimeoutBehaviour, Trigger};
+
+use std::collections::HashMap;
+
+/// Stack counts of conditional mod stats, keyed by mod name and stat index.
+/// Counts may be fractional when they are time-weighted averages.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Stacks {
+    stacks: HashMap<(String, usize), f32>,
+}
+
+impl Stacks {
+    /// Every conditional stat at its maximum stack count, or active if it
+    /// doesn't stack.
+    pub fn max(mods: &[Mod]) -> Self {
+        let mut stacks = Self::default();
+        for r#mod in mods {
+            for (index, stat) in r#mod.stats.iter().enumerate() {
+                if stat.is_conditional() {
+                    let max = stat.stacking.as_ref().map(|s| s.max).unwrap_or(1);
+                    stacks.set(&r#mod.name, index, max as f32);
+                }
+            }
+        }
+        stacks
+    }
+
+    pub fn set(&mut self, mod_name: &str, stat: usize, stacks: f32) {
+        self.stacks.insert((mod_name.to_owned(), stat), stacks);
+    }
+
+    pub fn get(&self, mod_name: &str, stat: usize) -> f32 {
+        self.stacks
+            .get(&(mod_name.to_owned(), stat))
+            .copied()
+            .unwrap_or(0.)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TrackedStat {
+    mod_name: String,
+    index: usize,
+    trigger: Option<Trigger>,
+    max: u32,
+    behaviour: Option<StackingBehaviour>,
+    stacks: u32,
+    expires: Option<f32>,
+}
+
+impl TrackedStat {
+    fn duration(&self) -> Option<f32> {
+        match &self.behaviour {
+            Some(StackingBehaviour::Timed { duration, .. }) => Some(duration.as_secs_f32()),
+            _ => None,
+        }
+    }
+
+    fn on_trigger(&mut self, now: f32) {
+        let was_active = self.stacks > 0;
+        self.stacks = (self.stacks + 1).min(self.max);
+
+        if let Some(duration) = self.duration() {
+            let resets = matches!(
+                self.behaviour,
+                Some(StackingBehaviour::Timed {
+                    resets_on_stack: true,
+                    ..
+                })
+            );
+            if resets || !was_active {
+                self.expires = Some(now + duration);
+            }
+        }
+    }
+
+    fn on_timeout(&mut self) {
+        let (Some(expires), Some(duration)) = (self.expires, self.duration()) else {
+            return;
+        };
+        let Some(StackingBehaviour::Timed { timeout, .. }) = &self.behaviour else {
+            return;
+        };
+
+        let lost = match timeout {
+            TimeoutBehaviour::Drop => self.stacks,
+            TimeoutBehaviour::Reduce(ReduceAmount::Flat(amount)) => *amount,
+            TimeoutBehaviour::Reduce(ReduceAmount::Relative(fraction)) => {
+                ((self.stacks as f32 * fraction).ceil() as u32).max(1)
+            }
+        };
+        self.stacks = self.stacks.saturating_sub(lost);
+        self.expires = (self.stacks > 0).then_some(expires + duration);
+    }
+}
+
+/// Time-stepped tracker of the stack count of every conditional mod stat,
+/// driven by `Trigger` events.
+#[derive(Debug, Clone)]
+pub struct StackEngine {
+    stats: Vec<TrackedStat>,
+    now: f32,
+}
+
+impl StackEngine {
+    pub fn new(mods: &[Mod]) -> Self {
+        let stats = mods
+            .iter()
+            .flat_map(|r#mod| {
+                r#mod
+                    .stats
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, stat)| stat.is_conditional())
+                    .map(|(index, stat)| TrackedStat {
+                        mod_name: r#mod.name.clone(),
+                        index,
+                        trigger: stat.trigger.clone(),
+                        max: stat.stacking.as_ref().map(|s| s.max).unwrap_or(1),
+                        behaviour: stat.stacking.as_ref().map(|s| s.behaviour.clone()),
+                        stacks: 0,
+                        expires: None,
+                    })
+            })
+            .collect();
+
+        Self { stats, now: 0. }
+    }
+
+    /// Applies every timeout up to `now`.
+    pub fn advance(&mut self, now: f32) {
+        while let Some(expires) = self.next_timeout()
+            && expires <= now
+        {
+            for stat in self.stats.iter_mut() {
+                if stat.expires == Some(expires) {
+                    stat.on_timeout();
+                }
+            }
+        }
+        self.now = now;
+    }
+
+    /// Advances to `now` and adds a stack to every stat listening for `trigger`.
+    pub fn trigger(&mut self, trigger: &Trigger, now: f32) {
+        self.advance(now);
+        for stat in self.stats.iter_mut() {
+            if stat.trigger.as_ref() == Some(trigger) {
+                stat.on_trigger(now);
+            }
+        }
+    }
+
+    fn next_timeout(&self) -> Option<f32> {
+        self.stats
+            .iter()
+            .filter_map(|stat| stat.expires)
+            .min_by(f32::total_cmp)
+    }
+
+    pub fn stacks(&self) -> Stacks {
+        let mut stacks = Stacks::default();
+        for stat in self.stats.iter() {
+            stacks.set(&stat.mod_name, stat.index, stat.stacks as f32);
+        }
+        stacks
+    }
+
+    /// Time-weighted average stack counts over `duration` seconds when each
+    /// trigger happens at a fixed rate, given in events per second.
+    pub fn average(mods: &[Mod], rates: &[(Trigger, f32)], duration: f32) -> Stacks {
+        let mut engine = Self::new(mods);
+        let mut events = rates
+            .iter()
+            .filter(|(_, rate)| *rate > 0.)
+            .flat_map(|(trigger, rate)| {
+                (0..)
+                    .map(move |i| i as f32 / rate)
+                    .take_while(move |time| *time < duration)
+                    .map(move |time| (time, trigger.clone()))
+            })
+            .collect::<Vec<_>>();
+        events.sort_by(|(l, _), (r, _)| l.total_cmp(r));
+
+        let mut totals = vec![0.; engine.stats.len()];
+        let mut accumulate = |engine: &mut Self, until: f32| {
+            // Step through timeouts so stacks are weighted by how long they last.
+            while engine.now < until {
+                let next = engine
+                    .next_timeout()
+                    .filter(|expires| *expires < until)
+                    .unwrap_or(until);
+                for (total, stat) in totals.iter_mut().zip(engine.stats.iter()) {
+                    *total += stat.stacks as f32 * (next - engine.now);
+                }
+                engine.advance(next);
+            }
+        };
+
+        for (time, trigger) in events {
+            accumulate(&mut engine, time);
+            engine.trigger(&trigger, time);
+        }
+        accumulate(&mut engine, duration);
+
+        let mut stacks = Stacks::default();
+        for (total, stat) in totals.into_iter().zip(engine.stats.iter()) {
+            stacks.set(&stat.mod_name, stat.index, total / duration);
+        }
+        stacks
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mods::ModLibrary;
+    use assert_float_eq::assert_f32_near;
+
+    use std::path::Path;
+
+    fn galvanized_chamber() -> Mod {
+        ModLibrary::load(Path::new("data/mods.ron"))
+            .get("Galvanized Chamber")
+            .expect("Galvanized Chamber should exist")
+            .clone()
+    }
+
+    #[test]
+    fn stacks_decay() {
+        let mods = vec![galvanized_chamber()];
+        let mut engine = StackEngine::new(&mods);
+
+        for kill in 0..7 {
+            engine.trigger(&Trigger::Kill, kill as f32);
+        }
+        assert_f32_near!(engine.stacks().get("Galvanized Chamber", 1), 5.);
+
+        // Each kill resets the timer, then stacks drop one at a time every 20s.
+        engine.advance(6. + 20.);
+        assert_f32_near!(engine.stacks().get("Galvanized Chamber", 1), 4.);
+        engine.advance(6. + 60.);
+        assert_f32_near!(engine.stacks().get("Galvanized Chamber", 1), 2.);
+        engine.advance(6. + 100.);
+        assert_f32_near!(engine.stacks().get("Galvanized Chamber", 1), 0.);
+    }
+
+    #[test]
+    fn average_stacks() {
+        let mods = vec![galvanized_chamber()];
+
+        // One kill every 10s over 50s: 1, 2, 3, 4 then 5 stacks, 10s each.
+        let stacks = StackEngine::average(&mods, &[(Trigger::Kill, 0.1)], 50.);
+        assert_f32_near!(stacks.get("Galvanized Chamber", 1), 3.);
+
+        assert_eq!(
+            StackEngine::average(&mods, &[], 50.),
+            StackEngine::new(&mods).stacks()
+        );
+        assert_f32_near!(Stacks::max(&mods).get("Galvanized Chamber", 1), 5.);
+    }
+}
@@ -0,0 +1,248 @@
+use crate::damage::*;
+
+use std::collections::HashMap;
+
+/// Seconds between two damage over time ticks.
+pub const TICK_INTERVAL: f32 = 1.;
+
+/// Fraction of a hit's modded base damage dealt by each tick of a damage over
+/// time proc, or `None` if the proc does not deal damage over time.
+pub fn dot_multiplier(damage_type: &DamageType) -> Option<f32> {
+    use DamageType::*;
+    use Element::*;
+    use PrimaryElement::*;
+    use SecondaryElement::*;
+
+    match damage_type {
+        Physical(Ips::Slash) => Some(0.35),
+        Elemental(Primary(Heat | Toxin | Electricity) | Secondary(Gas)) => Some(0.5),
+        _ => None,
+    }
+}
+
+/// Damage type dealt by the ticks of a damage over time proc. Bleeds deal
+/// True damage, which ignores armor and shields.
+pub fn dot_damage_type(damage_type: &DamageType) -> DamageType {
+    match damage_type {
+        DamageType::Physical(Ips::Slash) => DamageType::Special(Special::True),
+        other => *other,
+    }
+}
+
+/// Unmodded duration of a proc, in seconds.
+pub fn base_duration(damage_type: &DamageType) -> f32 {
+    match damage_type {
+        DamageType::Elemental(Element::Secondary(SecondaryElement::Corrosive)) => 8.,
+        DamageType::Elemental(Element::Secondary(SecondaryElement::Radiation)) => 12.,
+        _ => 6.,
+    }
+}
+
+/// Maximum number of simultaneously active stacks, or `None` if unlimited.
+pub fn max_stacks(damage_type: &DamageType) -> Option<u32> {
+    match damage_type {
+        _ if dot_multiplier(damage_type).is_some() => None,
+        DamageType::Elemental(Element::Primary(PrimaryElement::Cold)) => Some(9),
+        _ => Some(10),
+    }
+}
+
+/// Bonus granted by a debuff with `stacks` active stacks, where the first stack
+/// grants `first` and each additional one `per_stack`. Fractional stacks, such
+/// as time-weighted averages, interpolate linearly.
+fn stacked_bonus(stacks: f32, first: f32, per_stack: f32) -> f32 {
+    if stacks <= 0. {
+        0.
+    } else if stacks < 1. {
+        stacks * first
+    } else {
+        first + (stacks - 1.) * per_stack
+    }
+}
+
+/// Active status stacks on an enemy and the debuffs they apply.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Debuffs {
+    stacks: HashMap<DamageType, f32>,
+}
+
+impl Debuffs {
+    pub fn with_stacks(mut self, damage_type: DamageType, stacks: f32) -> Self {
+        self.set(damage_type, stacks);
+        self
+    }
+
+    pub fn set(&mut self, damage_type: DamageType, stacks: f32) {
+        let stacks = match max_stacks(&damage_type) {
+            Some(max) => stacks.min(max as f32),
+            None => stacks,
+        };
+        self.stacks.insert(damage_type, stacks);
+    }
+
+    pub fn stacks(&self, damage_type: &DamageType) -> f32 {
+        self.stacks.get(damage_type).copied().unwrap_or(0.)
+    }
+
+    fn element_stacks(&self, element: Element) -> f32 {
+        self.stacks(&DamageType::Elemental(element))
+    }
+
+    /// Viral: +100% health damage, +25% per additional stack.
+    pub fn health_multiplier(&self) -> f32 {
+        let viral = self.element_stacks(Element::Secondary(SecondaryElement::Viral));
+        1. + stacked_bonus(viral, 1., 0.25)
+    }
+
+    /// Magnetic: +100% shield damage, +25% per additional stack.
+    pub fn shield_multiplier(&self) -> f32 {
+        let magnetic = self.element_stacks(Element::Secondary(SecondaryElement::Magnetic));
+        1. + stacked_bonus(magnetic, 1., 0.25)
+    }
+
+    /// Corrosive strips 26% armor, +6% per additional stack, and any Heat proc
+    /// strips half of the remaining armor.
+    pub fn armor_multiplier(&self) -> f32 {
+        let corrosive = self.element_stacks(Element::Secondary(SecondaryElement::Corrosive));
+        let heat = self.element_stacks(Element::Primary(PrimaryElement::Heat));
+        (1. - stacked_bonus(corrosive, 0.26, 0.06)) * (1. - 0.5 * heat.min(1.))
+    }
+
+    /// Cold: +10% critical damage, +5% per additional stack.
+    pub fn critical_damage_bonus(&self) -> f32 {
+        let cold = self.element_stacks(Element::Primary(PrimaryElement::Cold));
+        stacked_bonus(cold, 0.1, 0.05)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ActiveProc {
+    damage_type: DamageType,
+    applied: f32,
+    expires: f32,
+    tick_damage: f32,
+    next_tick: f32,
+}
+
+/// Time-stepped tracker of the procs applied to an enemy.
+#[derive(Debug, Default, Clone)]
+pub struct StatusEngine {
+    procs: Vec<ActiveProc>,
+}
+
+impl StatusEngine {
+    /// Applies a proc at `now` lasting `duration` seconds. Damage over time
+    /// procs tick immediately and then every `TICK_INTERVAL` while active.
+    /// Procs past their stack cap replace the oldest active one.
+    pub fn apply(&mut self, damage_type: DamageType, now: f32, duration: f32, tick_damage: f32) {
+        if let Some(max) = max_stacks(&damage_type) {
+            let active = self
+                .procs
+                .iter()
+                .filter(|p| p.damage_type == damage_type)
+                .count();
+            if active >= max as usize
+                && let Some(oldest) = self
+                    .procs
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, p)| p.damage_type == damage_type)
+                    .min_by(|(_, l), (_, r)| l.applied.total_cmp(&r.applied))
+                    .map(|(i, _)| i)
+            {
+                self.procs.remove(oldest);
+            }
+        }
+
+        self.procs.push(ActiveProc {
+            damage_type,
+            applied: now,
+            expires: now + duration,
+            tick_damage,
+            next_tick: now,
+        });
+    }
+
+    /// Advances to `now`, returning the damage over time ticks that happened
+    /// since the last call and dropping expired procs.
+    pub fn advance(&mut self, now: f32) -> Vec<(DamageType, f32)> {
+        let mut ticks = vec![];
+
+        for proc in self.procs.iter_mut() {
+            if dot_multiplier(&proc.damage_type).is_none() {
+                continue;
+            }
+            while proc.next_tick <= now && proc.next_tick <= proc.expires {
+                ticks.push((dot_damage_type(&proc.damage_type), proc.tick_damage));
+                proc.next_tick += TICK_INTERVAL;
+            }
+        }
+
+        self.procs.retain(|proc| proc.expires > now);
+
+        ticks
+    }
+
+    /// Time of the next damage over time tick, if any is pending.
+    pub fn next_tick(&self) -> Option<f32> {
+        self.procs
+            .iter()
+            .filter(|proc| dot_multiplier(&proc.damage_type).is_some())
+            .filter(|proc| proc.next_tick <= proc.expires)
+            .map(|proc| proc.next_tick)
+            .min_by(f32::total_cmp)
+    }
+
+    pub fn debuffs(&self) -> Debuffs {
+        let mut debuffs = Debuffs::default();
+        for proc in self.procs.iter() {
+            let stacks = debuffs.stacks(&proc.damage_type);
+            debuffs.set(proc.damage_type, stacks + 1.);
+        }
+        debuffs
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use assert_float_eq::assert_f32_near;
+
+    const VIRAL: DamageType = DamageType::Elemental(Element::Secondary(SecondaryElement::Viral));
+    const SLASH: DamageType = DamageType::Physical(Ips::Slash);
+
+    #[test]
+    fn debuff_caps() {
+        let debuffs = Debuffs::default()
+            .with_stacks(VIRAL, 14.)
+            .with_stacks(
+                DamageType::Elemental(Element::Secondary(SecondaryElement::Corrosive)),
+                10.,
+            )
+            .with_stacks(
+                DamageType::Elemental(Element::Primary(PrimaryElement::Cold)),
+                1.,
+            );
+
+        assert_f32_near!(debuffs.health_multiplier(), 4.25);
+        assert_f32_near!(debuffs.armor_multiplier(), 0.2, 8);
+        assert_f32_near!(debuffs.critical_damage_bonus(), 0.1);
+        assert_f32_near!(debuffs.shield_multiplier(), 1.);
+    }
+
+    #[test]
+    fn dot_ticks() {
+        let mut engine = StatusEngine::default();
+        engine.apply(SLASH, 0., 6., 10.);
+        engine.apply(VIRAL, 0., 6., 0.);
+
+        let ticks = engine.advance(0.);
+        assert_eq!(ticks, vec![(DamageType::Special(Special::True), 10.)]);
+        assert_f32_near!(engine.debuffs().health_multiplier(), 2.);
+
+        // One tick per second, up to and including the proc's expiry.
+        assert_eq!(engine.advance(10.).len(), 6);
+        assert_eq!(engine.next_tick(), None);
+        assert_eq!(engine.debuffs(), Debuffs::default());
+    }
+}
@@ -0,0 +1,288 @@
+use crate::{
+    damage::*,
+    enemy,
+    mods::ModEffect,
+    warframe::{WarframeLoadout, WarframeStats},
+};
+
+use std::collections::HashMap;
+
+/// Invulnerability granted when full shields break, in seconds.
+const SHIELD_GATE: f32 = 1.3;
+
+/// Attacks of an enemy against a Warframe.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Incoming {
+    pub damage_type: DamageType,
+    /// Damage per hit at `base_level`.
+    pub damage: f32,
+    pub hits_per_second: f32,
+    pub base_level: u32,
+    pub level: u32,
+}
+
+impl Incoming {
+    /// Damage per hit at the enemy's level.
+    pub fn scaled_damage(&self) -> f32 {
+        let x = self.level.saturating_sub(self.base_level) as f32;
+        self.damage * (1. + 0.015 * x.powf(1.55))
+    }
+}
+
+/// Effective health against a damage type, without and with the shield gate.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct EffectiveHealth {
+    pub damage_type: DamageType,
+    pub ungated: f32,
+    /// Counts the hit that breaks shields in full, as the gate absorbs it.
+    pub gated: f32,
+}
+
+/// Pools and defenses of a Warframe against incoming damage.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Survivability {
+    pub health: f32,
+    pub shield: f32,
+    pub armor: f32,
+    /// Seconds of invulnerability when shields break.
+    pub shield_gate: f32,
+    /// Share of damage still taken after every damage reduction, which
+    /// stack multiplicatively.
+    pub damage_taken: f32,
+    /// Resistance to each damage type.
+    pub resistances: HashMap<DamageType, f32>,
+}
+
+impl Survivability {
+    pub fn new(stats: &WarframeStats) -> Self {
+        Self {
+            health: stats.health,
+            shield: stats.shield,
+            armor: stats.armor,
+            shield_gate: SHIELD_GATE,
+            damage_taken: 1.,
+            resistances: HashMap::new(),
+        }
+    }
+
+    /// Survivability of a loadout, with its mods' damage reductions and
+    /// resistances.
+    pub fn of(loadout: &WarframeLoadout) -> Self {
+        let mut survivability = Self::new(&loadout.stats());
+
+        for effect in loadout.effects() {
+            match effect {
+                ModEffect::DamageReduction(value) => {
+                    survivability = survivability.with_damage_reduction(value)
+                }
+                ModEffect::ShieldGateDuration(value) => {
+                    survivability.shield_gate *= 1. + value;
+                }
+                ModEffect::ElementResistance(element, value) => {
+                    survivability.add_resistance(DamageType::Elemental(element), value);
+                }
+                ModEffect::PhysicalDamageResistance(value) => {
+                    for ips in [Ips::Impact, Ips::Puncture, Ips::Slash] {
+                        survivability.add_resistance(DamageType::Physical(ips), value);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        survivability
+    }
+
+    /// Adds a damage reduction, such as an ability's, on top of the others.
+    pub fn with_damage_reduction(mut self, reduction: f32) -> Self {
+        self.damage_taken *= 1. - reduction.clamp(0., 1.);
+        self
+    }
+
+    fn add_resistance(&mut self, damage_type: DamageType, value: f32) {
+        *self.resistances.entry(damage_type).or_insert(0.) += value;
+    }
+
+    pub fn armor_reduction(&self) -> f32 {
+        enemy::armor_reduction(self.armor)
+    }
+
+    /// Whether damage of this type skips shields and goes straight to health.
+    pub fn bypasses_shield(damage_type: &DamageType) -> bool {
+        matches!(
+            damage_type,
+            DamageType::Elemental(Element::Primary(PrimaryElement::Toxin))
+        )
+    }
+
+    /// Share of damage of a type that reaches the shield and health pools.
+    fn taken(&self, damage_type: &DamageType) -> (f32, f32) {
+        let resistance = self.resistances.get(damage_type).copied().unwrap_or(0.);
+        let taken = self.damage_taken * (1. - resistance.min(1.));
+        let armor = match damage_type {
+            DamageType::Special(Special::True) => 1.,
+            _ => 1. - self.armor_reduction(),
+        };
+        (taken, taken * armor)
+    }
+
+    /// Raw damage of a type it takes to down the Warframe, ignoring shield
+    /// gating.
+    pub fn effective_health(&self, damage_type: &DamageType) -> f32 {
+        let (shield_taken, health_taken) = self.taken(damage_type);
+        let health = self.health / health_taken;
+        if Self::bypasses_shield(damage_type) || self.shield <= 0. {
+            health
+        } else {
+            health + self.shield / shield_taken
+        }
+    }
+
+    /// Raw damage of a type it takes to down the Warframe with hits of
+    /// `hit` raw damage, the one breaking shields being absorbed whole by the
+    /// shield gate.
+    pub fn gated_effective_health(&self, damage_type: &DamageType, hit: f32) -> f32 {
+        let (shield_hits, health_hits) = self.hits_to_down_with(damage_type, hit);
+        (shield_hits + health_hits) * hit
+    }
+
+    /// Effective health against every damage type, for hits of `hit` raw
+    /// damage.
+    pub fn effective_health_table(&self, hit: f32) -> Vec<EffectiveHealth> {
+        [Ips::Impact, Ips::Puncture, Ips::Slash]
+            .into_iter()
+            .map(DamageType::Physical)
+            .chain(Element::ALL.into_iter().map(DamageType::Elemental))
+            .chain(
+                [Special::Void, Special::Tau, Special::True]
+                    .into_iter()
+                    .map(DamageType::Special),
+            )
+            .map(|damage_type| EffectiveHealth {
+                damage_type,
+                ungated: self.effective_health(&damage_type),
+                gated: self.gated_effective_health(&damage_type, hit),
+            })
+            .collect()
+    }
+
+    /// Hits it takes to break shields, the last one being absorbed whole by
+    /// the shield gate, and then to empty health.
+    pub fn hits_to_down(&self, incoming: &Incoming) -> (f32, f32) {
+        self.hits_to_down_with(&incoming.damage_type, incoming.scaled_damage())
+    }
+
+    fn hits_to_down_with(&self, damage_type: &DamageType, hit: f32) -> (f32, f32) {
+        let (shield_taken, health_taken) = self.taken(damage_type);
+        let shield_hits = if Self::bypasses_shield(damage_type) || self.shield <= 0. {
+            0.
+        } else {
+            (self.shield / (hit * shield_taken)).ceil()
+        };
+        (shield_hits, self.health / (hit * health_taken))
+    }
+
+    /// Seconds it takes an enemy to down the Warframe, shield gate included.
+    pub fn time_to_down(&self, incoming: &Incoming) -> f32 {
+        let (shield_hits, health_hits) = self.hits_to_down(incoming);
+        let gate = if shield_hits > 0. {
+            self.shield_gate
+        } else {
+            0.
+        };
+        (shield_hits + health_hits.ceil() - 1.).max(0.) / incoming.hits_per_second + gate
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        mods::{Compatibility, Mod, ModStat},
+        warframe::WarframeLibrary,
+    };
+
+    use assert_float_eq::assert_f32_near;
+    use std::path::Path;
+
+    #[test]
+    fn effective_health() {
+        let warframes = WarframeLibrary::load(Path::new("data/warframes.ron"));
+        let mut loadout = WarframeLoadout::new(
+            warframes
+                .get("Excalibur")
+                .expect("Excalibur should exist")
+                .clone(),
+        );
+        loadout
+            .equip(Mod {
+                name: "Steel Fiber".to_owned(),
+                stats: vec![ModStat::from(ModEffect::Armor(1.))],
+                compatibility: Compatibility::Other("Warframe".to_owned()),
+                ..Default::default()
+            })
+            .expect("Steel Fiber should fit");
+
+        // 450 armor reduces health damage by 60%.
+        let survivability = Survivability::of(&loadout);
+        let impact = DamageType::Physical(Ips::Impact);
+        let toxin = DamageType::Elemental(Element::Primary(PrimaryElement::Toxin));
+        let r#true = DamageType::Special(Special::True);
+        assert_f32_near!(survivability.armor_reduction(), 0.6);
+        assert_f32_near!(survivability.effective_health(&impact), 945.);
+        assert_f32_near!(survivability.effective_health(&toxin), 675.);
+        assert_f32_near!(survivability.effective_health(&r#true), 540.);
+
+        // Damage reductions stack multiplicatively.
+        let reduced = survivability
+            .clone()
+            .with_damage_reduction(0.5)
+            .with_damage_reduction(0.5);
+        assert_f32_near!(reduced.effective_health(&impact), 945. * 4.);
+
+        // The gate absorbs the whole hit breaking shields, however big.
+        let table = survivability.effective_health_table(1000.);
+        let row = |damage_type: DamageType| {
+            *table
+                .iter()
+                .find(|row| row.damage_type == damage_type)
+                .expect("Every damage type should have a row")
+        };
+        assert_f32_near!(row(impact).ungated, 945.);
+        assert_f32_near!(row(impact).gated, 1000. + 675.);
+        assert_f32_near!(row(toxin).gated, 675.);
+        assert_f32_near!(
+            survivability.gated_effective_health(&impact, 100.),
+            300. + 675.
+        );
+
+        let incoming = Incoming {
+            damage_type: impact,
+            damage: 1000.,
+            hits_per_second: 2.,
+            base_level: 1,
+            level: 1,
+        };
+        // A single hit breaks shields whatever its damage, and the gate
+        // absorbs it.
+        let (shield_hits, health_hits) = survivability.hits_to_down(&incoming);
+        assert_f32_near!(shield_hits, 1.);
+        assert_f32_near!(health_hits, 0.675, 8);
+        assert_f32_near!(survivability.time_to_down(&incoming), 0.5 + SHIELD_GATE);
+
+        let incoming = Incoming {
+            level: 101,
+            ..incoming
+        };
+        assert!(survivability.hits_to_down(&incoming).1 < 0.675 / 10.);
+
+        // Unlike enemy armor, Warframe armor keeps reducing damage past 2700.
+        let tank = Survivability {
+            armor: 5700.,
+            ..survivability
+        };
+        assert_f32_near!(tank.armor_reduction(), 0.95);
+        assert_f32_near!(tank.effective_health(&r#true), 540.);
+        assert_f32_near!(tank.effective_health(&impact), 270. / 0.05 + 270.);
+    }
+}
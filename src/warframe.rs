@@ -0,0 +1,414 @@
+use crate::{
+    arcane::{Arcane, ArcaneSlot},
+    build::LoadoutError,
+    mods::{Compatibility, Mod, ModEffect},
+    public_export::warframe::{self as export, ProductCategory},
+    stacking::Stacks,
+};
+
+use std::{collections::HashMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+/// Regular mod slots, aura and exilus included.
+pub const MOD_SLOTS: usize = 10;
+pub const ARCANE_SLOTS: usize = 2;
+
+pub const MAX_EFFICIENCY: f32 = 1.75;
+pub const MIN_DURATION: f32 = 0.125;
+pub const MIN_RANGE: f32 = 0.34;
+
+/// Base stats of a Warframe, as the Public Export lists them.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct Warframe {
+    pub name: String,
+    pub health: f32,
+    pub shield: f32,
+    pub armor: f32,
+    pub energy: f32,
+    pub sprint_speed: f32,
+}
+
+impl Warframe {
+    pub fn from_export(warframe: &export::Warframe) -> Self {
+        Self {
+            name: warframe.name.clone(),
+            health: warframe.health,
+            shield: warframe.shield,
+            armor: warframe.armor,
+            energy: warframe.power,
+            sprint_speed: warframe.sprint_speed,
+        }
+    }
+}
+
+pub struct WarframeLibrary {
+    warframes: HashMap<String, Warframe>,
+}
+
+impl WarframeLibrary {
+    pub fn load(path: &Path) -> Self {
+        Self::try_load(path).expect("Should load Warframe library")
+    }
+
+    /// Like `load`, but returns an error instead of panicking if the file
+    /// cannot be read or deserialized.
+    pub fn try_load(path: &Path) -> Result<Self, String> {
+        let file_content = std::fs::read_to_string(path)
+            .map_err(|error| format!("Cannot read {}: {error}", path.display()))?;
+        let warframes = ron::from_str::<Vec<Warframe>>(file_content.as_str())
+            .map_err(|error| format!("Cannot deserialize {}: {error}", path.display()))?;
+        Ok(Self {
+            warframes: warframes.into_iter().map(|w| (w.name.clone(), w)).collect(),
+        })
+    }
+
+    /// Builds a library from the Public Export's Warframes, leaving out
+    /// Archwings and Necramechs.
+    pub fn from_export(warframes: &[export::Warframe]) -> Self {
+        Self {
+            warframes: warframes
+                .iter()
+                .filter(|warframe| warframe.product_category == ProductCategory::Suits)
+                .map(|warframe| (warframe.name.clone(), Warframe::from_export(warframe)))
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, warframe_name: &str) -> Option<&Warframe> {
+        self.warframes.get(warframe_name)
+    }
+}
+
+/// A stat before and after its cap or minimum.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Stat {
+    pub value: f32,
+    pub uncapped: f32,
+}
+
+impl Stat {
+    fn new(uncapped: f32, min: f32, max: f32) -> Self {
+        Self {
+            value: uncapped.clamp(min, max),
+            uncapped,
+        }
+    }
+
+    pub fn is_capped(&self) -> bool {
+        self.value != self.uncapped
+    }
+}
+
+/// Final stats of a Warframe loadout. Ability stats are multipliers, 1
+/// meaning 100%.
+#[derive(Debug, PartialEq, Clone)]
+pub struct WarframeStats {
+    pub health: f32,
+    pub shield: f32,
+    pub armor: f32,
+    pub energy: f32,
+    pub sprint_speed: f32,
+    pub strength: Stat,
+    /// Capped at `MAX_EFFICIENCY`.
+    pub efficiency: Stat,
+    /// At least `MIN_DURATION`.
+    pub duration: Stat,
+    /// At least `MIN_RANGE`.
+    pub range: Stat,
+}
+
+impl WarframeStats {
+    /// Multiplier of ability energy costs.
+    pub fn cost_multiplier(&self) -> f32 {
+        2. - self.efficiency.value
+    }
+}
+
+/// A Warframe with its equipped mods and arcanes.
+#[derive(Debug, Clone)]
+pub struct WarframeLoadout {
+    pub warframe: Warframe,
+    mods: Vec<Mod>,
+    arcanes: Vec<Arcane>,
+    stacks: Stacks,
+}
+
+impl WarframeLoadout {
+    pub fn new(warframe: Warframe) -> Self {
+        Self {
+            warframe,
+            mods: vec![],
+            arcanes: vec![],
+            stacks: Stacks::default(),
+        }
+    }
+
+    pub fn mods(&self) -> &[Mod] {
+        &self.mods
+    }
+
+    pub fn arcanes(&self) -> &[Arcane] {
+        &self.arcanes
+    }
+
+    /// Warframe mods, general or augments of this Warframe, are accepted.
+    /// Duplicates and mods from the same family as another equipped mod are
+    /// rejected.
+    pub fn equip(&mut self, r#mod: Mod) -> Result<(), LoadoutError> {
+        let compatible = match &r#mod.compatibility {
+            Compatibility::Any => true,
+            Compatibility::Other(name) => {
+                name.eq_ignore_ascii_case("Warframe")
+                    || name.eq_ignore_ascii_case("Aura")
+                    || *name == self.warframe.name
+            }
+            _ => false,
+        };
+        if !compatible {
+            return Err(LoadoutError::Incompatible(r#mod.name));
+        }
+        if self.mods.len() >= MOD_SLOTS {
+            return Err(LoadoutError::SlotsFull(r#mod.name));
+        }
+        for equipped in self.mods.iter() {
+            if equipped.name == r#mod.name {
+                return Err(LoadoutError::DuplicateMod(r#mod.name));
+            }
+            if let Some(family) = &r#mod.family
+                && equipped.family.as_ref() == Some(family)
+            {
+                return Err(LoadoutError::SameFamily {
+                    family: family.clone(),
+                    equipped: equipped.name.clone(),
+                });
+            }
+        }
+
+        self.mods.push(r#mod);
+        Ok(())
+    }
+
+    pub fn equip_arcane(&mut self, arcane: Arcane) -> Result<(), LoadoutError> {
+        if arcane.slot != ArcaneSlot::Warframe {
+            return Err(LoadoutError::Incompatible(arcane.name));
+        }
+        if self.arcanes.len() >= ARCANE_SLOTS {
+            return Err(LoadoutError::SlotsFull(arcane.name));
+        }
+        if self.arcanes.iter().any(|a| a.name == arcane.name) {
+            return Err(LoadoutError::DuplicateMod(arcane.name));
+        }
+        self.arcanes.push(arcane);
+        Ok(())
+    }
+
+    /// Sets the stack count of conditional stats, which otherwise don't apply.
+    pub fn with_stacks(mut self, stacks: Stacks) -> Self {
+        self.stacks = stacks;
+        self
+    }
+
+    /// Mods followed by arcanes, as `Stacks` expects them.
+    pub fn all_mods(&self) -> Vec<Mod> {
+        self.mods
+            .iter()
+            .cloned()
+            .chain(self.arcanes.iter().map(Arcane::to_mod))
+            .collect()
+    }
+
+    pub(crate) fn effects(&self) -> Vec<ModEffect> {
+        self.all_mods()
+            .into_iter()
+            .flat_map(|r#mod| {
+                r#mod
+                    .ranked_stats()
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(index, stat)| {
+                        if !stat.is_conditional() {
+                            return Some(stat.effect);
+                        }
+                        let stacks = self.stacks.get(&r#mod.name, index);
+                        (stacks > 0.).then(|| stat.effect.scaled(stacks))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn sum_effects(&self, select: impl Fn(ModEffect) -> Option<f32>) -> f32 {
+        self.effects().into_iter().filter_map(select).sum()
+    }
+
+    pub fn stats(&self) -> WarframeStats {
+        let sum = |select: fn(ModEffect) -> Option<f32>| 1. + self.sum_effects(select);
+        let warframe = &self.warframe;
+
+        WarframeStats {
+            health: warframe.health
+                * sum(|e| match e {
+                    ModEffect::HealthCapacity(v) => Some(v),
+                    _ => None,
+                }),
+            shield: warframe.shield
+                * sum(|e| match e {
+                    ModEffect::ShieldCapacity(v) => Some(v),
+                    _ => None,
+                }),
+            armor: warframe.armor
+                * sum(|e| match e {
+                    ModEffect::Armor(v) => Some(v),
+                    _ => None,
+                }),
+            energy: warframe.energy
+                * sum(|e| match e {
+                    ModEffect::Energy(v) => Some(v),
+                    _ => None,
+                }),
+            sprint_speed: warframe.sprint_speed
+                * sum(|e| match e {
+                    ModEffect::SprintSpeed(v) => Some(v),
+                    _ => None,
+                }),
+            strength: Stat::new(
+                sum(|e| match e {
+                    ModEffect::AbilityStrength(v) => Some(v),
+                    _ => None,
+                }),
+                0.,
+                f32::INFINITY,
+            ),
+            efficiency: Stat::new(
+                sum(|e| match e {
+                    ModEffect::AbilityEfficiency(v) => Some(v),
+                    _ => None,
+                }),
+                f32::NEG_INFINITY,
+                MAX_EFFICIENCY,
+            ),
+            duration: Stat::new(
+                sum(|e| match e {
+                    ModEffect::AbilityDuration(v) => Some(v),
+                    _ => None,
+                }),
+                MIN_DURATION,
+                f32::INFINITY,
+            ),
+            range: Stat::new(
+                sum(|e| match e {
+                    ModEffect::AbilityRange(v) => Some(v),
+                    _ => None,
+                }),
+                MIN_RANGE,
+                f32::INFINITY,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{mods::ModStat, public_export::warframe::ExportWarframes};
+
+    use assert_float_eq::assert_f32_near;
+
+    fn warframe_mod(name: &str, effects: Vec<ModEffect>) -> Mod {
+        Mod {
+            name: name.to_owned(),
+            stats: effects.into_iter().map(ModStat::from).collect(),
+            compatibility: Compatibility::Other("Warframe".to_owned()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stat_caps() {
+        let warframes = WarframeLibrary::load(Path::new("data/warframes.ron"));
+        let excalibur = warframes.get("Excalibur").expect("Excalibur should exist");
+        let mut loadout = WarframeLoadout::new(excalibur.clone());
+
+        for r#mod in [
+            warframe_mod("Streamline", vec![ModEffect::AbilityEfficiency(0.3)]),
+            warframe_mod(
+                "Fleeting Expertise",
+                vec![
+                    ModEffect::AbilityEfficiency(0.6),
+                    ModEffect::AbilityDuration(-0.6),
+                ],
+            ),
+            warframe_mod(
+                "Narrow Minded",
+                vec![
+                    ModEffect::AbilityDuration(-0.4),
+                    ModEffect::AbilityRange(-0.66),
+                ],
+            ),
+            warframe_mod("Vitality", vec![ModEffect::HealthCapacity(4.4)]),
+        ] {
+            loadout.equip(r#mod).expect("Warframe mods should fit");
+        }
+        let serration = Mod {
+            name: "Serration".to_owned(),
+            compatibility: Compatibility::Rifle,
+            ..Default::default()
+        };
+        assert!(loadout.equip(serration).is_err());
+
+        let stats = loadout.stats();
+        assert_f32_near!(stats.efficiency.value, MAX_EFFICIENCY);
+        assert_f32_near!(stats.efficiency.uncapped, 1.9);
+        assert_f32_near!(stats.cost_multiplier(), 0.25);
+        assert_f32_near!(stats.duration.value, MIN_DURATION);
+        assert_f32_near!(stats.range.value, MIN_RANGE);
+        assert!(!stats.strength.is_capped());
+        assert_f32_near!(stats.health, excalibur.health * 5.4);
+    }
+
+    #[test]
+    fn full_slots() {
+        let warframes = WarframeLibrary::load(Path::new("data/warframes.ron"));
+        let excalibur = warframes.get("Excalibur").expect("Excalibur should exist");
+        let mut loadout = WarframeLoadout::new(excalibur.clone());
+
+        for index in 0..MOD_SLOTS {
+            loadout
+                .equip(warframe_mod(&format!("Mod {index}"), vec![]))
+                .expect("Warframe mods should fit");
+        }
+        assert_eq!(
+            loadout.equip(warframe_mod("Vitality", vec![])),
+            Err(LoadoutError::SlotsFull("Vitality".to_owned()))
+        );
+
+        let arcane = |name: &str| Arcane {
+            name: name.to_owned(),
+            slot: ArcaneSlot::Warframe,
+            ..Default::default()
+        };
+        for index in 0..ARCANE_SLOTS {
+            loadout
+                .equip_arcane(arcane(&format!("Arcane {index}")))
+                .expect("Warframe arcanes should fit");
+        }
+        assert_eq!(
+            loadout.equip_arcane(arcane("Arcane Energize")),
+            Err(LoadoutError::SlotsFull("Arcane Energize".to_owned()))
+        );
+    }
+
+    #[test]
+    fn export_warframes() {
+        let json = std::fs::read_to_string("data/public_export/ExportWarframes_en.json")
+            .expect("Should read sample");
+        let export =
+            serde_json::from_str::<ExportWarframes>(&json).expect("Should deserialize sample");
+        let warframes = WarframeLibrary::from_export(&export.export_warframes);
+
+        let excalibur = warframes.get("Excalibur").expect("Excalibur should exist");
+        assert_f32_near!(excalibur.energy, 100.);
+        assert!(warframes.get("Odonata").is_none());
+    }
+}
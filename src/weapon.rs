@@ -1,8 +1,112 @@
+use crate::{
+    damage::DamageType,
+    public_export::{convert, weapon as export},
+};
+
+use std::{collections::HashMap, path::Path};
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Weapon {
-    ty: WeaponType,
+    pub name: String,
+    pub mastery_rank: u32,
+    pub ty: WeaponType,
+    pub attacks: Vec<Attack>,
+    /// Progenitor bonus of Kuva and Tenet weapons.
+    #[serde(default)]
+    pub progenitor: Option<Progenitor>,
+}
+
+impl Weapon {
+    pub fn attack(&self, name: &str) -> Option<&Attack> {
+        self.attacks.iter().find(|attack| attack.name == name)
+    }
+}
+
+pub struct WeaponLibrary {
+    weapons: HashMap<String, Weapon>,
+    /// Display names of the weapons by unique name, for exported weapons.
+    unique_names: HashMap<String, String>,
+}
+
+impl WeaponLibrary {
+    pub fn load(path: &Path) -> Self {
+        Self::try_load(path).expect("Should load weapon library")
+    }
+
+    /// Like `load`, but returns an error instead of panicking if the file
+    /// cannot be read or deserialized.
+    pub fn try_load(path: &Path) -> Result<Self, String> {
+        let file_content = std::fs::read_to_string(path)
+            .map_err(|error| format!("Cannot read {}: {error}", path.display()))?;
+        let weapons = ron::from_str::<Vec<Weapon>>(file_content.as_str())
+            .map_err(|error| format!("Cannot deserialize {}: {error}", path.display()))?;
+        Ok(Self {
+            weapons: weapons.into_iter().map(|w| (w.name.clone(), w)).collect(),
+            unique_names: HashMap::new(),
+        })
+    }
+
+    /// Builds a library from the Public Export's weapons, skipping those that
+    /// cannot be equipped.
+    pub fn from_export(exported: &[export::Weapon]) -> Self {
+        let mut weapons = HashMap::new();
+        let mut unique_names = HashMap::new();
+
+        for exported in exported {
+            if let Some(weapon) = convert::convert_weapon(exported) {
+                unique_names.insert(exported.unique_name.clone(), weapon.name.clone());
+                weapons.insert(weapon.name.clone(), weapon);
+            }
+        }
+
+        Self {
+            weapons,
+            unique_names,
+        }
+    }
+
+    /// Looks a weapon up by display name, or by unique name such as
+    /// `/Lotus/Weapons/Tenno/Rifle/BratonPrime`.
+    pub fn get(&self, weapon_name: &str) -> Option<&Weapon> {
+        self.weapons.get(weapon_name).or_else(|| {
+            self.unique_names
+                .get(weapon_name)
+                .and_then(|name| self.weapons.get(name))
+        })
+    }
+}
+
+/// Bonus damage of a Kuva or Tenet weapon, inherited from its Lich or Sister.
+/// The bonus is a share of the weapon's unmodded total base damage, between 25%
+/// and 60%, and then counts as innate base damage of its type.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy)]
+pub struct Progenitor {
+    pub damage_type: DamageType,
+    pub bonus: f32,
+}
+
+/// A single firing mode or attack of a weapon, with its unmodded stats.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct Attack {
+    pub name: String,
+    pub base_damage: HashMap<DamageType, f32>,
+    pub critical_chance: f32,
+    pub critical_multiplier: f32,
+    pub status_chance: f32,
+    /// Shots per second.
+    pub fire_rate: f32,
+    /// Seconds to charge before each shot, for bows and other charged attacks.
+    #[serde(default)]
+    pub charge_time: f32,
+    pub multishot: f32,
+    pub magazine: u32,
+    /// Reload time in seconds.
+    pub reload: f32,
+    /// Reserve ammo, `None` if the attack never runs out.
+    #[serde(default)]
+    pub ammo: Option<u32>,
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
@@ -106,3 +210,18 @@ pub enum RailjackWeaponType {
     Ordnance,
     Turret,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn load_weapons() {
+        let weapons = WeaponLibrary::load(Path::new("data/weapons.ron"));
+        let kuva_bramma = weapons
+            .get("Kuva Bramma")
+            .expect("Kuva Bramma should exist");
+        assert!(kuva_bramma.progenitor.is_some());
+        assert!(kuva_bramma.attack("Charged Shot").is_some());
+    }
+}